@@ -0,0 +1,275 @@
+use serde_json::Value;
+
+/// ID of the element that is used to announce messages from Rust to screen readers.
+pub const POLITE_REGION_ID: &'static str = "uitacoLivePolite";
+
+/// ID of the element that is used to announce urgent messages from Rust to screen readers.
+pub const ASSERTIVE_REGION_ID: &'static str = "uitacoLiveAssertive";
+
+/// CSS selector of the elements that can receive focus.
+pub const FOCUSABLE_SELECTOR: &'static str = "a[href], area[href], button:not([disabled]), \
+    input:not([disabled]), select:not([disabled]), textarea:not([disabled]), \
+    iframe, [tabindex]:not([tabindex='-1']), [contenteditable='true']";
+
+/// WAI-ARIA role of the element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Role {
+    Alert,
+    AlertDialog,
+    Button,
+    Checkbox,
+    Dialog,
+    Grid,
+    Link,
+    List,
+    ListItem,
+    Log,
+    Menu,
+    MenuItem,
+    Navigation,
+    Presentation,
+    ProgressBar,
+    Radio,
+    Region,
+    Status,
+    Tab,
+    TabList,
+    TabPanel,
+    TextBox,
+    Tooltip,
+    Tree,
+    TreeItem,
+
+    /// Role that is not listed here.
+    Custom(String),
+}
+
+/// Value of ARIA states like `aria-checked` or `aria-pressed` which allow mixed state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Tristate {
+    True,
+    False,
+    Mixed,
+}
+
+/// How urgently screen reader should announce changes of the live region.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Politeness {
+
+    /// Changes are not announced.
+    Off,
+
+    /// Changes are announced when user is idle.
+    Polite,
+
+    /// Changes are announced immediately.
+    Assertive,
+}
+
+impl Role {
+
+    /// Value of the `role` attribute.
+    pub fn name(&self) -> &str {
+        use Role::*;
+        match self {
+            Alert           => "alert",
+            AlertDialog     => "alertdialog",
+            Button          => "button",
+            Checkbox        => "checkbox",
+            Dialog          => "dialog",
+            Grid            => "grid",
+            Link            => "link",
+            List            => "list",
+            ListItem        => "listitem",
+            Log             => "log",
+            Menu            => "menu",
+            MenuItem        => "menuitem",
+            Navigation      => "navigation",
+            Presentation    => "presentation",
+            ProgressBar     => "progressbar",
+            Radio           => "radio",
+            Region          => "region",
+            Status          => "status",
+            Tab             => "tab",
+            TabList         => "tablist",
+            TabPanel        => "tabpanel",
+            TextBox         => "textbox",
+            Tooltip         => "tooltip",
+            Tree            => "tree",
+            TreeItem        => "treeitem",
+
+            Custom(s)       => s,
+        }
+    }
+}
+
+impl From<&str> for Role {
+
+    fn from(s: &str) -> Self {
+        use Role::*;
+        match s.to_lowercase().as_str() {
+            "alert"         => Alert,
+            "alertdialog"   => AlertDialog,
+            "button"        => Button,
+            "checkbox"      => Checkbox,
+            "dialog"        => Dialog,
+            "grid"          => Grid,
+            "link"          => Link,
+            "list"          => List,
+            "listitem"      => ListItem,
+            "log"           => Log,
+            "menu"          => Menu,
+            "menuitem"      => MenuItem,
+            "navigation"    => Navigation,
+            "presentation"  => Presentation,
+            "progressbar"   => ProgressBar,
+            "radio"         => Radio,
+            "region"        => Region,
+            "status"        => Status,
+            "tab"           => Tab,
+            "tablist"       => TabList,
+            "tabpanel"      => TabPanel,
+            "textbox"       => TextBox,
+            "tooltip"       => Tooltip,
+            "tree"          => Tree,
+            "treeitem"      => TreeItem,
+
+            _               => Custom(String::from(s)),
+        }
+    }
+}
+
+impl Tristate {
+
+    /// Value of the ARIA attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tristate::True  => "true",
+            Tristate::False => "false",
+            Tristate::Mixed => "mixed",
+        }
+    }
+}
+
+impl From<bool> for Tristate {
+
+    fn from(b: bool) -> Self {
+        if b {
+            Tristate::True
+        } else {
+            Tristate::False
+        }
+    }
+}
+
+impl Politeness {
+
+    /// Value of the `aria-live` attribute.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Politeness::Off         => "off",
+            Politeness::Polite      => "polite",
+            Politeness::Assertive   => "assertive",
+        }
+    }
+}
+
+/// JS code that announces given text through the hidden live region. Region is created
+/// on first use. Text is reset first so that repeated messages get announced too.
+/// Messages that are not to be announced give no code.
+pub fn announce_js(text: &str, politeness: Politeness) -> String {
+    let id = match politeness {
+        Politeness::Off => return String::new(),
+        Politeness::Polite => POLITE_REGION_ID,
+        Politeness::Assertive => ASSERTIVE_REGION_ID,
+    };
+
+    format!("\
+        var r = document.getElementById('{id}');\
+        if (r == null) {{\
+            r = document.createElement('div');\
+            r.id = '{id}';\
+            r.setAttribute('aria-atomic', 'true');\
+            r.style.position = 'absolute';\
+            r.style.width = '1px';\
+            r.style.height = '1px';\
+            r.style.overflow = 'hidden';\
+            r.style.clip = 'rect(0 0 0 0)';\
+            document.body.appendChild(r);\
+        }}\
+        r.setAttribute('aria-live', '{live}');\
+        r.textContent = '';\
+        setTimeout(function() {{ r.textContent = {text}; }}, 50);\
+    ", id = id, live = politeness.name(), text = Value::String(text.to_owned()))
+}
+
+/// JS code that keeps keyboard focus inside of the element with given ID. Tab and Shift+Tab
+/// cycle through focusable descendants of the element.
+pub fn trap_focus_js(id: &str) -> String {
+    format!("\
+        var e = document.getElementById('{id}');\
+        if (e.uitacoFocusTrap) {{\
+            e.removeEventListener('keydown', e.uitacoFocusTrap);\
+        }}\
+        e.uitacoFocusTrap = function(ev) {{\
+            if (ev.key !== 'Tab' && ev.keyCode !== 9) {{\
+                return;\
+            }}\
+            var list = e.querySelectorAll(\"{sel}\");\
+            if (list.length == 0) {{\
+                ev.preventDefault();\
+                return;\
+            }}\
+            var first = list[0];\
+            var last = list[list.length - 1];\
+            if (ev.shiftKey && document.activeElement === first) {{\
+                last.focus();\
+                ev.preventDefault();\
+            }} else if (!ev.shiftKey && document.activeElement === last) {{\
+                first.focus();\
+                ev.preventDefault();\
+            }}\
+        }};\
+        e.addEventListener('keydown', e.uitacoFocusTrap);\
+        if (!e.contains(document.activeElement)) {{\
+            var list = e.querySelectorAll(\"{sel}\");\
+            if (list.length > 0) {{\
+                list[0].focus();\
+            }}\
+        }}\
+    ", id = id, sel = FOCUSABLE_SELECTOR)
+}
+
+/// JS code that removes focus trap previously set on the element with given ID.
+pub fn release_focus_js(id: &str) -> String {
+    format!("\
+        var e = document.getElementById('{id}');\
+        if (e.uitacoFocusTrap) {{\
+            e.removeEventListener('keydown', e.uitacoFocusTrap);\
+            e.uitacoFocusTrap = undefined;\
+        }}\
+    ", id = id)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::aria::{announce_js, Politeness, Role, Tristate, POLITE_REGION_ID};
+
+    #[test]
+    fn announcements() {
+        assert!(announce_js("hidden", Politeness::Off).is_empty());
+
+        let js = announce_js("line\nend \\", Politeness::Polite);
+        assert!(js.contains(POLITE_REGION_ID));
+        assert!(js.contains("r.setAttribute('aria-live', 'polite');r.textContent = '';"));
+        assert!(js.contains("r.textContent = \"line\\nend \\\\\";"));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(Role::from("TabList"), Role::TabList);
+        assert_eq!(Role::from("feed"), Role::Custom("feed".to_string()));
+        assert_eq!(Role::MenuItem.name(), "menuitem");
+        assert_eq!(Tristate::from(false).name(), "false");
+    }
+}
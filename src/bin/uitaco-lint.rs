@@ -0,0 +1,4 @@
+/// Check HTML files with component classes. Arguments are files and directories to check.
+fn main() {
+    std::process::exit(uitaco::lint::run(std::env::args().skip(1)));
+}
@@ -1,14 +1,26 @@
 use std::collections::{HashMap, HashSet, LinkedList};
 use crate::tags::{Element, TagName};
-use crate::{ViewWrap};
+use crate::{ViewWrap, Callback, CallbackId, ResponseValue};
 use std::sync::{Arc, RwLock};
 use std::fmt::Debug;
 use htmldom_read::{Node, NodeAccess, Attribute, Children};
 use owning_ref::{RwLockReadGuardRef, RwLockWriteGuardRefMut};
 use std::hash::{Hasher, Hash};
 use std::marker::PhantomData;
-use std::ops::{Deref, DerefMut};
-use rsgen::{OutputCharsType, gen_random_string};
+use crate::i18n::{self, Translations};
+use crate::aria;
+use crate::template::{self, Prop, PropsError};
+use serde_json::Value;
+use crate::registry::ClassRegistry;
+use crate::observable::{self, Observable, Binding};
+use crate::form::{self, FormError};
+use crate::diff;
+use crate::ids::{IdGenerator, IdStrategy};
+use crate::style;
+use crate::lint;
+use crate::query::{self, Selector, SelectorError};
+use crate::emit::{ComponentEvent, HandlerId};
+use serde::de::DeserializeOwned;
 
 /// This value must be stored in class attribute of tag which starts a component class.
 pub const COMPONENT_MARK: &'static str = "uitacoComponent";
@@ -17,6 +29,38 @@ pub const COMPONENT_MARK: &'static str = "uitacoComponent";
 /// his also removes it's HTML code from all nodes of loaded classes.
 pub const SKIP_ELEMENT_MARK: &'static str = "uitacoSkip";
 
+/// Whether the element with given value of the class attribute starts a component class.
+/// Elements which are marked to be skipped do not start a class.
+pub fn is_class_root(class_attr: &str) -> bool {
+    let mut values = class_attr.split_whitespace();
+    values.clone().any(|v| v == COMPONENT_MARK) && !values.any(|v| v == SKIP_ELEMENT_MARK)
+}
+
+/// Tag name of the element which marks the place where content can be projected into
+/// the instance of a class.
+pub const SLOT_TAG: &'static str = "slot";
+
+/// Tag of the elements in class HTML that contain code which is run for each instance
+/// after it was mounted. Code gets the root element of the instance in `root` variable and
+/// the map of original IDs of the elements to generated IDs in `ids` variable. `this` is
+/// the root element too.
+pub const SCRIPT_TAG: &'static str = "script";
+
+/// Name of the slot which has no `name` attribute.
+pub const DEFAULT_SLOT: &'static str = "";
+
+/// Attribute of the element which content is replaced with an instance of the class
+/// with given name: `<div data-uitaco-use="userCard">`.
+pub const USE_ATTR: &'static str = "data-uitaco-use";
+
+/// Attribute with the path to the properties of the parent instance which are passed to
+/// the referenced class instance: `<div data-uitaco-use="userCard" data-uitaco-props="user">`.
+pub const USE_PROPS_ATTR: &'static str = "data-uitaco-props";
+
+/// Attribute that marks the element to which sub-components can be mounted:
+/// `<ul data-uitaco-anchor="items">`.
+pub const ANCHOR_ATTR: &'static str = "data-uitaco-anchor";
+
 pub type ClassHandle = Arc<Class>;
 pub type ComponentId = usize;
 
@@ -33,6 +77,93 @@ pub enum AddComponentError {
 
 pub trait CustomAddComponentError: Debug {}
 
+/// Error that occur while loading classes with checks.
+#[derive(Debug)]
+pub enum ClassLoadError {
+
+    /// HTML cannot be parsed or is empty.
+    InvalidHtml,
+
+    /// Node is marked as component but class cannot be created from it, likely because
+    /// it has no ID. HTML code of the node is stored.
+    InvalidClass(String),
+
+    /// Two classes have the same name.
+    Duplicate(String),
+}
+
+/// Error that occur while mounting component to the anchor.
+#[derive(Debug)]
+pub enum MountError {
+
+    /// Class has no anchor with given name.
+    UnknownAnchor,
+
+    /// Sibling is not mounted to the same anchor.
+    UnknownSibling,
+
+    /// Component is not mounted to any anchor of this container.
+    NotMounted,
+
+    /// Component cannot be added.
+    Add(AddComponentError),
+}
+
+/// Error that occur while building an instance of the class.
+#[derive(Debug)]
+pub enum BuildError {
+
+    /// Required properties of the instance were not given.
+    Props(PropsError),
+
+    /// Element references a class with given name which is not known.
+    UnknownClass(String),
+
+    /// Class with given name is referenced from it's own instance.
+    Cycle(String),
+
+    /// Properties given to the referenced class by path cannot be found or are not
+    /// an object.
+    UnresolvedProps {
+        class: String,
+        path: String,
+    },
+
+    /// Instance of the referenced class with given name cannot be built.
+    Use {
+        class: String,
+        error: Box<BuildError>,
+    },
+}
+
+/// Position of the mounted component among other content of the anchor.
+#[derive(Clone, Debug)]
+pub enum MountPosition {
+
+    /// Put component after all content of the anchor.
+    Append,
+
+    /// Put component before all content of the anchor.
+    Prepend,
+
+    /// Put component right before the sibling mounted to the same anchor.
+    Before(ComponentHandle),
+
+    /// Put component right after the sibling mounted to the same anchor.
+    After(ComponentHandle),
+}
+
+/// Error that occur while filling slot of the instance.
+#[derive(Debug)]
+pub enum FillSlotError {
+
+    /// Class has no slot with given name.
+    UnknownSlot,
+
+    /// Given HTML cannot be parsed.
+    InvalidHtml,
+}
+
 /// If you want to implement custom error use this trait and "Custom" variant of
 /// `ChildrenLogicError`.
 pub trait ChildrenLogicAddCustomError: Debug {}
@@ -109,11 +240,138 @@ pub trait Component: Element + Container + ChildrenLogic {
     /// Component class from which it was instantiated.
     fn class(&self) -> &ClassHandle;
 
+    /// Base component which this component wraps. Hooks that manage elements, scripts and
+    /// mounted sub-components are forwarded to it by default, so components built on top
+    /// of `ComponentBase` should return it here. Base component gives itself.
+    fn base(&self) -> Option<&ComponentBase> {
+        None
+    }
+
+    /// Mutable access to the base component which this component wraps.
+    fn base_mut(&mut self) -> Option<&mut ComponentBase> {
+        None
+    }
+
+    /// Let all elements of the component release resources they registered in the view.
+    /// Called when the component gets removed from the interface.
+    fn release_elements(&mut self) {
+        if let Some(base) = self.base_mut() {
+            base.release_elements();
+        }
+    }
+
+    /// Called after HTML of the component was inserted into the page. JS code evaluated from
+    /// here runs after the insertion. Sub-components get this call after their parent.
+    fn on_mount(&mut self) {}
+
+    /// Run scripts of the class for this instance if they were not run yet. Called after
+    /// HTML of the component was inserted into the page, before `on_mount`.
+    fn run_scripts(&mut self) {
+        if let Some(base) = self.base_mut() {
+            base.run_scripts();
+        }
+    }
+
+    /// Called before the component gets removed from the interface.
+    /// Sub-components get this call before their parent.
+    fn on_unmount(&mut self) {}
+
+    /// Called when the component or one of it's parents becomes visible.
+    fn on_show(&mut self) {}
+
+    /// Called when the component or one of it's parents gets hidden.
+    fn on_hide(&mut self) {}
+
+    /// Called once when the page of the view has loaded for all components that exist
+    /// at that moment.
+    fn on_view_ready(&mut self) {}
+
+    /// User-defined state of the component that is saved to the snapshot.
+    fn save_state(&self) -> Option<Value> {
+        None
+    }
+
+    /// Apply user-defined state from the snapshot after the component was restored.
+    fn restore_state(&mut self, _state: Value) {}
+
+    /// Name of the anchor to which given sub-component is mounted and it's index among
+    /// other components of that anchor.
+    fn mounted_anchor(&self, component: &ComponentHandle) -> Option<(String, usize)> {
+        self.base().and_then(|base| base.mounted_anchor(component))
+    }
+
+    /// Components mounted to given anchor in the order they appear in HTML.
+    fn mounted(&self, anchor: &str) -> Option<&Vec<ComponentHandle>> {
+        self.base().and_then(|base| base.mounted(anchor))
+    }
+
+    /// Add the component to this container and insert it's HTML to the anchor. Components
+    /// without the base have no anchors.
+    fn mount(&mut self, anchor: &str, component: Box<dyn Component>, position: MountPosition)
+            -> Result<ComponentHandle, MountError> {
+        match self.base_mut() {
+            Some(base) => base.mount(anchor, component, position),
+            None => Err(MountError::UnknownAnchor),
+        }
+    }
+
+    /// Move the component mounted to any of the anchors to new position in the same anchor.
+    fn move_mounted(&mut self, component: &ComponentHandle, position: MountPosition)
+            -> Result<(), MountError> {
+        match self.base_mut() {
+            Some(base) => base.move_mounted(component, position),
+            None => Err(MountError::NotMounted),
+        }
+    }
+
+    /// Remove the component mounted to any of the anchors from this container and the page.
+    fn unmount(&mut self, component: &ComponentHandle) -> Option<()> {
+        self.base_mut().and_then(|base| base.unmount(component))
+    }
+
+    /// Find elements of the component that match CSS selector in it's generated HTML.
+    /// Elements that have no ID get a generated one. Sub-components are not searched.
+    fn query(&mut self, selector: &str) -> Result<Vec<Box<dyn Element>>, SelectorError> {
+        match self.base_mut() {
+            Some(base) => base.query(selector),
+            None => Ok(Vec::new()),
+        }
+    }
+
     /// Check whether this component is of given class.
     fn is_of_class(&self, class: &ClassHandle) -> bool {
         let this = self.class();
         Arc::ptr_eq(this, class)
     }
+
+    /// Keep keyboard focus inside of this component. Useful for dialogs and menus.
+    /// Focus moves to the first focusable element if it is outside of the component.
+    fn trap_focus(&self) {
+        self.view().eval(aria::trap_focus_js(self.name()));
+    }
+
+    /// Remove focus trap set by `trap_focus`.
+    fn release_focus(&self) {
+        self.view().eval(aria::release_focus_js(self.name()));
+    }
+}
+
+impl dyn Component {
+
+    /// Whether this component has given type.
+    pub fn is<T: Component + 'static>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Get this component if it has given type.
+    pub fn downcast_ref<T: Component + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Get mutable component if it has given type.
+    pub fn downcast_mut<T: Component + 'static>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
 }
 
 /// Perform more advanced component initialization.
@@ -136,8 +394,10 @@ pub trait Initialize {
 
     /// The third stage of initialization.
     ///
-    /// Load all sub-components and initialize them.
-    fn initialize_container(&mut self);
+    /// Load all sub-components and initialize them. Components referenced by class HTML
+    /// with `USE_ATTR` are already built by `InstanceBuilder` so by default there is
+    /// nothing left to do.
+    fn initialize_container(&mut self) {}
 
     /// The fourth stage of initialization.
     ///
@@ -179,10 +439,40 @@ pub struct Class {
     /// The name of the component class.
     name: String,
 
+    /// Name with the namespace under which the class was registered. Same as the name if
+    /// class was not registered.
+    full_name: String,
+
     /// Full HTML code of this component. All IDs are removed.
     html: Arc<Node>,
 
     placeholders: HashMap<String, Placeholder>,
+
+    /// Names of the slots found in the class HTML.
+    slots: HashSet<String>,
+
+    /// Properties referenced in text and attribute values of the class HTML.
+    /// References to the items of repeated blocks are not listed.
+    props: Vec<Prop>,
+
+    /// Whether class HTML contains conditional or repeated blocks.
+    has_directives: bool,
+
+    /// Names of the classes referenced by the class HTML.
+    uses: HashSet<String>,
+
+    /// Names of the anchors to which sub-components can be mounted.
+    anchors: HashSet<String>,
+
+    /// Styles of the class rewritten to apply only to it's elements.
+    style: Option<String>,
+
+    /// Styles of the class as they were written. Kept to scope them again when the class
+    /// gets the full name.
+    style_source: Option<String>,
+
+    /// Code of the scripts that is run for each instance after it was mounted.
+    scripts: Vec<String>,
 }
 
 /// Builder to instantiate component.
@@ -194,6 +484,21 @@ pub struct InstanceBuilder {
 
     /// Placeholders for this instance.
     placeholders: HashMap<String, Placeholder>,
+
+    /// HTML code to be projected into the slots. The key is the name of the slot.
+    slots: HashMap<String, String>,
+
+    /// Components that were projected into the slots. They become sub-components of the instance.
+    slot_components: Vec<ComponentHandle>,
+
+    /// Values of the properties to be put into class HTML.
+    props: Option<Value>,
+
+    /// Classes that can be referenced by class HTML. The key is a class name.
+    classes: HashMap<String, ClassHandle>,
+
+    /// Classes which instances contain this one. Used to break reference cycles.
+    parents: Vec<ClassHandle>,
 }
 
 /// Built component that is ready to be added to a page.
@@ -217,6 +522,15 @@ pub struct ComponentBase {
 
     /// Components that were added to this component.
     components: HashSet<ComponentHandle>,
+
+    /// Components mounted to each anchor in the order they appear in HTML.
+    anchors: HashMap<String, Vec<ComponentHandle>>,
+
+    /// Callback that is called when user changes any form control of this component.
+    form_callback: Option<CallbackId>,
+
+    /// Whether scripts of the class were run for this instance.
+    scripts_run: bool,
 }
 
 /// Handle to a component registered in the interface.
@@ -279,26 +593,11 @@ impl Placeholder {
     }
 
     /// Generate random name. Can be used when no exact name is necessary and it is enough that
-    /// this element just exists and is accessible by any name.
-    /// This is likely the way you would want to generate names.
+    /// this element just exists and is accessible by any name. Name is not reserved in
+    /// the view so it does not follow the ID strategy of the view and may repeat.
+    /// Use `InstanceBuilder::use_view_names` to get names that are reserved in the view.
     pub fn use_generated_name(&mut self) -> &String {
-        let len = 15;
-        let prefix = "autogen";
-        let mut s = String::with_capacity(15);
-        s.push_str(prefix);
-        let len = len - prefix.len();
-
-        // Generate random string.
-        let oct = OutputCharsType::LatinAlphabetAndNumeric {
-            use_lower_case: true,
-            use_upper_case: true
-        };
-        let random = gen_random_string(len, oct);
-        s.push_str(&random);
-
-        // Save name.
-        self.set_name(s);
-
+        self.set_name(IdGenerator::new(IdStrategy::Random).name());
         self.name().unwrap()
     }
 
@@ -394,6 +693,38 @@ impl Class {
             }
         };
 
+        // Styles are taken out of class HTML and all elements of the class get the scope
+        // class so the styles only apply to them.
+        let (node, style, style_source) = {
+            if has_tag(node.children(), style::STYLE_TAG) {
+                let scope = node.children().iter().next()
+                    .and_then(|n| n.attribute_by_name("id"))
+                    .map(|a| style::scope_name(a.first_value()));
+                let scope = if let Some(scope) = scope {
+                    scope
+                } else {
+                    return None;
+                };
+
+                let mut owned = owned_node(&node.to_string())?;
+                let mut css = String::new();
+                take_styles(owned.children_mut(), &scope, &mut css);
+                (Arc::new(owned), Some(style::scope_css(&css, &scope)), Some(css))
+            } else {
+                (node, None, None)
+            }
+        };
+
+        // Scripts are taken out of class HTML to be run for each instance.
+        let (node, scripts) = if has_tag(node.children(), SCRIPT_TAG) {
+            let mut owned = owned_node(&node.to_string())?;
+            let mut scripts = Vec::new();
+            take_scripts(owned.children_mut(), &mut scripts);
+            (Arc::new(owned), scripts)
+        } else {
+            (node, Vec::new())
+        };
+
         // Node must contain component mark and class name.
         let name = {
             // Get node that contains component tag.
@@ -405,7 +736,7 @@ impl Class {
 
             let class_attr = node.attribute_by_name("class");
             if let Some(class_attr) = class_attr {
-                if !class_attr.values().contains(&COMPONENT_MARK.to_string()) {
+                if !is_class_root(&class_attr.values_to_string()) {
                     return None;
                 }
             } else {
@@ -443,10 +774,72 @@ impl Class {
             map
         };
 
+        // Find all slot names.
+        let slots = {
+            fn collect(children: &Children, set: &mut HashSet<String>) {
+                for child in children.iter() {
+                    if let Some(name) = slot_name(child) {
+                        set.insert(name);
+                    }
+                    collect(child.children(), set);
+                }
+            }
+
+            let mut set = HashSet::new();
+            collect(node.children(), &mut set);
+            set
+        };
+
+        // Find all referenced classes.
+        let uses = {
+            let fetch = node.children_fetch()
+                .key(USE_ATTR);
+            let mut set = HashSet::new();
+            for node in fetch.fetch() {
+                set.insert(node.attribute_by_name(USE_ATTR).unwrap().first_value().to_owned());
+            }
+            set
+        };
+
+        // Find all anchors.
+        let anchors = {
+            let fetch = node.children_fetch()
+                .key(ANCHOR_ATTR);
+            let mut set = HashSet::new();
+            for node in fetch.fetch() {
+                set.insert(node.attribute_by_name(ANCHOR_ATTR).unwrap().first_value().to_owned());
+            }
+            set
+        };
+
+        // Properties that refer to items of repeated blocks are filled by the block itself.
+        let (props, has_directives) = {
+            let html = node.to_string();
+            let items = template::item_names_in(&html);
+            let props = template::props_in(&html).into_iter()
+                .filter(|p| {
+                    let first = p.path().split('.').next().unwrap();
+                    first != template::INDEX_NAME && !items.iter().any(|s| s == first)
+                })
+                .collect();
+            let has_directives = html.contains(template::IF_ATTR)
+                || html.contains(template::FOR_ATTR);
+            (props, has_directives)
+        };
+
         Some(Class {
             name: name.to_owned(),
+            full_name: name.to_owned(),
             html: node,
-            placeholders
+            placeholders,
+            slots,
+            props,
+            has_directives,
+            uses,
+            anchors,
+            style,
+            style_source,
+            scripts,
         })
     }
 
@@ -505,16 +898,115 @@ impl Class {
         map
     }
 
+    /// Load all classes from this HTML. Unlike `all_from_html` this function fails if any of
+    /// the nodes marked as component cannot be loaded or if class names repeat.
+    /// Nodes that are marked to be skipped are not treated as errors.
+    pub fn all_from_html_checked(html: &str) -> Result<HashMap<String, Class>, ClassLoadError> {
+        let node = Node::from_html(html, &Default::default());
+        let node = if let Ok(Some(node)) = node {
+            node
+        } else {
+            return Err(ClassLoadError::InvalidHtml);
+        };
+
+        let component_nodes = node.children_fetch()
+            .value_part(COMPONENT_MARK)
+            .key("class")
+            .fetch();
+
+        let mut map = HashMap::new();
+        for node in component_nodes {
+            let class_attr = node.attribute_by_name("class").unwrap().values_to_string();
+            if !is_class_root(&class_attr) {
+                continue;
+            }
+
+            let class = Class::try_one_from_node(node.to_sharable());
+            let class = if let Some(class) = class {
+                class
+            } else {
+                return Err(ClassLoadError::InvalidClass(node.to_string()));
+            };
+
+            if map.contains_key(&class.name) {
+                return Err(ClassLoadError::Duplicate(class.name));
+            }
+            map.insert(class.name.clone(), class);
+        }
+        Ok(map)
+    }
+
+    /// Check the HTML with classes for problems that would make them fail to load or
+    /// behave not as expected. Unlike loading functions this one reports all found problems
+    /// with their positions in the source.
+    pub fn validate(html: &str) -> Vec<lint::Diagnostic> {
+        lint::validate(html)
+    }
+
     /// Get name (id) of this class.
     pub fn name(&self) -> &String {
         &self.name
     }
 
+    /// Name with the namespace under which the class was registered.
+    pub fn full_name(&self) -> &String {
+        &self.full_name
+    }
+
+    /// Set the name with the namespace. Registry does this when the class is inserted.
+    /// Styles of the class are scoped by the full name, so classes with the same name
+    /// from different namespaces do not share styles.
+    pub fn set_full_name(&mut self, full_name: String) {
+        if let Some(css) = &self.style_source {
+            let old = style::scope_name(&self.full_name);
+            let new = style::scope_name(&full_name);
+            if old != new {
+                if let Some(mut html) = owned_node(&self.html.to_string()) {
+                    replace_class(html.children_mut(), &old, &new);
+                    self.html = Arc::new(html);
+                }
+                self.style = Some(style::scope_css(css, &new));
+            }
+        }
+        self.full_name = full_name;
+    }
+
     /// All placeholders of a class.
     pub fn placeholders(&self) -> &HashMap<String, Placeholder> {
         &self.placeholders
     }
 
+    /// Names of all slots of a class. Unnamed slot is listed as `DEFAULT_SLOT`.
+    pub fn slots(&self) -> &HashSet<String> {
+        &self.slots
+    }
+
+    /// Properties referenced by the class HTML.
+    pub fn props(&self) -> &Vec<Prop> {
+        &self.props
+    }
+
+    /// Names of the classes referenced by the class HTML.
+    pub fn used_classes(&self) -> &HashSet<String> {
+        &self.uses
+    }
+
+    /// Names of the anchors to which sub-components can be mounted.
+    pub fn anchors(&self) -> &HashSet<String> {
+        &self.anchors
+    }
+
+    /// Styles of the class rewritten to apply only to it's elements. They are injected
+    /// to the view when the first instance of the class is built.
+    pub fn style(&self) -> Option<&String> {
+        self.style.as_ref()
+    }
+
+    /// Code of the scripts of the class.
+    pub fn scripts(&self) -> &Vec<String> {
+        &self.scripts
+    }
+
     /// Create class handle from this owned class.
     pub fn into_handle(self) -> ClassHandle {
         Arc::new(self)
@@ -541,29 +1033,256 @@ impl InstanceBuilder {
         InstanceBuilder {
             class: handle,
             placeholders,
+            slots: Default::default(),
+            slot_components: Default::default(),
+            props: None,
+            classes: Default::default(),
+            parents: Default::default(),
         }
     }
 
+    /// Make the class available for references from class HTML. Referenced classes
+    /// are built with generated names of all elements and become sub-components of the
+    /// instance. Classes given to this builder are available to referenced instances too.
+    pub fn use_class(&mut self, class: ClassHandle) {
+        self.use_class_as(class.name().to_owned(), class);
+    }
+
+    /// Make the class available for references from class HTML by given name.
+    pub fn use_class_as(&mut self, name: String, class: ClassHandle) {
+        self.classes.insert(name, class);
+    }
+
+    /// Set properties to fill class HTML with. Properties must serialize to an object.
+    /// Values get escaped before they are put into HTML.
+    ///
+    /// # Failure
+    /// All required properties of the class must be present.
+    pub fn with_props<T: serde::Serialize>(mut self, props: &T) -> Result<Self, PropsError> {
+        let value = template::props_to_value(props)?;
+        template::check_props(&self.class.props, &value)?;
+        self.props = Some(value);
+        Ok(self)
+    }
+
     /// Get element by HTML ID.
     pub fn element_by_id_mut(&mut self, id: &str) -> Option<&mut Placeholder> {
         self.placeholders.get_mut(id)
     }
 
-    /// Build the component for given interface.
+    /// Generate random names for all placeholders of the instance. Names are not reserved
+    /// in the view so they do not follow the ID strategy of the view and may repeat.
+    /// Use `use_view_names` to get names that are reserved in the view.
+    pub fn use_generated_names(&mut self) {
+        let mut generator = IdGenerator::new(IdStrategy::Random);
+        self.use_names_from(&mut generator);
+    }
+
+    /// Generate names for all placeholders of the instance with given generator.
+    pub fn use_names_from(&mut self, generator: &mut IdGenerator) {
+        let class = self.class.name();
+        let mut origins: Vec<&str> = self.placeholders.keys()
+            .filter(|k| *k != class)
+            .map(|k| k.as_str())
+            .collect();
+        origins.sort();
+        origins.insert(0, class);
+
+        let names = generator.instance(class, &origins);
+        let origins: Vec<String> = origins.into_iter().map(|s| s.to_owned()).collect();
+        for (origin, name) in origins.into_iter().zip(names) {
+            self.placeholders.get_mut(&origin).unwrap().set_name(name);
+        }
+    }
+
+    /// Generate names for all placeholders of the instance with the ID strategy of the view.
+    pub fn use_view_names(&mut self, view: &ViewWrap) {
+        let mut guard = view.inner.view.write().unwrap();
+        self.use_names_from(&mut guard.ids);
+    }
+
+    /// Append HTML code to the slot with given name. Filled slot loses it's default content.
+    pub fn fill_slot_html(&mut self, slot: &str, html: &str) -> Result<(), FillSlotError> {
+        if !self.class.slots.contains(slot) {
+            return Err(FillSlotError::UnknownSlot);
+        }
+        if let Err(_) = Node::from_html(html, &Default::default()) {
+            return Err(FillSlotError::InvalidHtml);
+        }
+
+        if let Some(s) = self.slots.get_mut(slot) {
+            s.push_str(html);
+        } else {
+            self.slots.insert(slot.to_owned(), html.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Append the node and it's children to the slot with given name.
+    pub fn fill_slot_node(&mut self, slot: &str, node: &Node) -> Result<(), FillSlotError> {
+        self.fill_slot_html(slot, &node.to_string())
+    }
+
+    /// Append generated HTML of the component to the slot with given name. The component
+    /// becomes a sub-component of the built instance.
+    pub fn fill_slot_component(&mut self, slot: &str, component: &ComponentHandle)
+            -> Result<(), FillSlotError> {
+        let html = component.read().generated_html().to_string();
+        self.fill_slot_html(slot, &html)?;
+        self.slot_components.push(component.clone());
+        Ok(())
+    }
+
+    /// Build the component for given interface. If no properties were given with
+    /// `with_props` then the text of the class is left as is, otherwise properties that
+    /// were not given are replaced with empty strings. Use `try_build` to check that
+    /// all required properties are present.
+    /// References to other classes that cannot be built are left empty.
     pub fn build(self, view: ViewWrap) -> ComponentBase {
+        let interpolate = self.props.is_some();
+        match self.build_with(view, interpolate, false) {
+            Ok(base) => base,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Build the component for given interface.
+    ///
+    /// # Failure
+    /// All required properties of the class must be given with `with_props`. Optional
+    /// properties that were not given are replaced with empty strings. All classes
+    /// referenced by the class HTML must be known, must not contain the class itself
+    /// and must get all their required properties.
+    pub fn try_build(self, view: ViewWrap) -> Result<ComponentBase, BuildError> {
+        self.build_checked(view, true)
+    }
+
+    /// Check the properties and build the component. References to other classes fail
+    /// the build only if `strict` is set.
+    fn build_checked(self, view: ViewWrap, strict: bool) -> Result<ComponentBase, BuildError> {
+        let checked = if let Some(props) = &self.props {
+            template::check_props(&self.class.props, props)
+        } else {
+            template::check_props(&self.class.props, &Value::Object(Default::default()))
+        };
+        checked.map_err(|e| BuildError::Props(e))?;
+        self.build_with(view, true, strict)
+    }
+
+    /// Build the component without checking the properties. Properties are filled in
+    /// the text only if `interpolate` is set. Unless `strict` is set references to other
+    /// classes that cannot be built are left empty and the build does not fail.
+    fn build_with(self, view: ViewWrap, interpolate: bool, strict: bool)
+            -> Result<ComponentBase, BuildError> {
         let class = self.class;
+        let data = if let Some(props) = self.props {
+            props
+        } else {
+            Value::Object(Default::default())
+        };
+
+        if let Some(css) = &class.style {
+            view.inject_class_css(class.full_name(), css);
+        }
+
+        // Generated IDs of the repeated elements mapped to the initial IDs of the class.
+        let mut origins = HashMap::new();
 
         let mut html = {
             let mut html = class.html.as_ref().to_owned();
             let owned = html.children().to_all_owned();
             *html.children_mut() = owned;
-            html
+
+            // Directives of the component root element itself are not evaluated.
+            if class.has_directives {
+                for child in html.children_mut().iter_mut() {
+                    if let Some(node) = child.try_mut() {
+                        expand_directives(node.children_mut(), &data, &mut origins);
+                    }
+                }
+            }
+
+            if !interpolate || (class.props.is_empty() && !class.has_directives) {
+                html
+            } else {
+                // Fill properties in HTML code and parse it again.
+                let s = template::interpolate(&html.to_string(), &data);
+                owned_node(&s).unwrap()
+            }
+        };
+
+        // Default content of the filled slots is dropped before elements are registered so
+        // it's elements and referenced classes do not become a part of the instance.
+        if !self.slots.is_empty() {
+            fn clear(children: &mut Children, slots: &HashMap<String, String>) {
+                for child in children.iter_mut() {
+                    let node = if let Some(node) = child.try_mut() {
+                        node
+                    } else {
+                        continue;
+                    };
+
+                    let filled = slot_name(node).map(|name| slots.contains_key(&name))
+                        .unwrap_or(false);
+                    if filled {
+                        while node.children().get(0).is_some() {
+                            node.children_mut().remove(0);
+                        }
+                    } else {
+                        clear(node.children_mut(), slots);
+                    }
+                }
+            }
+
+            clear(html.children_mut(), &self.slots);
+        }
+
+        // Elements removed by conditions or with slot content lose their placeholders and
+        // repeated elements get placeholders derived from the initial ones.
+        let placeholders = if class.has_directives || !self.slots.is_empty() {
+            let ids = {
+                fn collect(children: &Children, set: &mut HashSet<String>) {
+                    for child in children.iter() {
+                        if let Some(attr) = child.attribute_by_name("id") {
+                            set.insert(attr.first_value().to_owned());
+                        }
+                        collect(child.children(), set);
+                    }
+                }
+
+                let mut set = HashSet::new();
+                collect(html.children(), &mut set);
+                set
+            };
+
+            let mut map = HashMap::with_capacity(self.placeholders.len());
+            for (id, origin) in origins {
+                if !ids.contains(&id) {
+                    continue;
+                }
+                if let Some(ph) = self.placeholders.get(&origin) {
+                    let suffix = &id[origin.len()..];
+                    let new = ph.new.as_ref().map(|s| format!("{}{}", s, suffix));
+                    map.insert(id.clone(), Placeholder {
+                        initial: id,
+                        new,
+                    });
+                }
+            }
+            for (initial, ph) in self.placeholders {
+                if ids.contains(&initial) {
+                    map.insert(initial, ph);
+                }
+            }
+            map
+        } else {
+            self.placeholders
         };
 
         let elements = {
             let mut elements
-                = HashMap::with_capacity(class.placeholders.len());
-            for ph in self.placeholders {
+                = HashMap::with_capacity(placeholders.len());
+            for ph in placeholders {
                 // Get node.
                 let initial = ph.0;
                 let ph = ph.1;
@@ -598,13 +1317,389 @@ impl InstanceBuilder {
             elements
         };
 
-        ComponentBase {
-            view,
-            class,
+        // Build referenced classes. Elements of the instance are already registered so
+        // generated HTML of nested components is not mistaken for them.
+        let mut components: HashSet<ComponentHandle>
+            = self.slot_components.into_iter().collect();
+        if !class.uses.is_empty() {
+            let mut parents = self.parents;
+            parents.push(class.clone());
+
+            let registry = view.class_registry();
+            let ctx = UseContext {
+                view: &view,
+                classes: &self.classes,
+                registry: registry.as_ref().map(|r| r.as_ref()),
+                parents: &parents,
+                data: &data,
+                strict,
+            };
+            let mut used = HashSet::new();
+            let result = build_used(html.children_mut(), &ctx, &mut used);
+            if let Err(e) = result {
+                // Instances that were already built are not needed.
+                for component in &used {
+                    view.inner.view.write().unwrap().remove_component(component);
+                    view.teardown_component(component);
+                }
+                return Err(e);
+            }
+            components.extend(used);
+        }
+
+        // Project content into the slots.
+        if !self.slots.is_empty() {
+            fn fill(children: &mut Children, slots: &HashMap<String, Node>) {
+                for child in children.iter_mut() {
+                    let node = if let Some(node) = child.try_mut() {
+                        node
+                    } else {
+                        continue;
+                    };
+
+                    let content = slot_name(node).and_then(|name| slots.get(&name));
+                    if let Some(content) = content {
+                        *node.children_mut() = content.children().to_all_owned();
+                    } else {
+                        fill(node.children_mut(), slots);
+                    }
+                }
+            }
+
+            let mut slots = HashMap::with_capacity(self.slots.len());
+            for (name, s) in self.slots {
+                // HTML was checked when slot was filled.
+                if let Some(node) = Node::from_html(&s, &Default::default()).unwrap() {
+                    slots.insert(name, node);
+                }
+            }
+            fill(html.children_mut(), &slots);
+        }
+
+        // Translate text of the elements which are marked with translation keys.
+        {
+            let guard = view.inner.view.read().unwrap();
+            translate_node(&mut html, &guard.translations);
+        }
+
+        Ok(ComponentBase {
+            view,
             html,
             elements,
-            components: Default::default(),
+            components,
+            anchors: class.anchors.iter().map(|a| (a.to_owned(), Vec::new())).collect(),
+            form_callback: None,
+            scripts_run: false,
+            class,
+        })
+    }
+}
+
+/// Data that is needed to build classes referenced by class HTML.
+struct UseContext<'a> {
+    view: &'a ViewWrap,
+    classes: &'a HashMap<String, ClassHandle>,
+    registry: Option<&'a ClassRegistry>,
+    parents: &'a Vec<ClassHandle>,
+    data: &'a Value,
+
+    /// Whether references that cannot be built fail the build.
+    strict: bool,
+}
+
+/// Replace content of elements that reference classes with new instances of those classes
+/// and register built components in the view. Classes given to the builder are looked up first
+/// and then the class registry of the view. Unknown classes, classes that would contain
+/// themselves and classes that cannot be built give an error in strict mode and are left
+/// empty otherwise.
+fn build_used(children: &mut Children, ctx: &UseContext,
+              components: &mut HashSet<ComponentHandle>) -> Result<(), BuildError> {
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let name = node.attribute_by_name(USE_ATTR)
+            .map(|attr| attr.first_value().to_owned());
+        let name = if let Some(name) = name {
+            name
+        } else {
+            build_used(node.children_mut(), ctx, components)?;
+            continue;
+        };
+        let fail = |e| if ctx.strict { Err(e) } else { Ok(()) };
+
+        // The same class can be referenced by different names so cycles are found by identity.
+        let class = ctx.classes.get(&name)
+            .or_else(|| ctx.registry.and_then(|r| r.get(&name)));
+        let class = match class {
+            Some(class) if ctx.parents.iter().any(|p| Arc::ptr_eq(p, class)) => {
+                fail(BuildError::Cycle(name))?;
+                continue;
+            },
+            Some(class) => class,
+            None => {
+                fail(BuildError::UnknownClass(name))?;
+                continue;
+            },
+        };
+
+        let mut builder = InstanceBuilder::new_for_handle(class.clone());
+        builder.classes = ctx.classes.clone();
+        builder.parents = ctx.parents.clone();
+        builder.use_view_names(ctx.view);
+        if let Some(attr) = node.attribute_by_name(USE_PROPS_ATTR) {
+            let path = attr.first_value();
+            match template::lookup(ctx.data, path) {
+                Some(props) if props.is_object() => builder.props = Some(props.clone()),
+                _ => fail(BuildError::UnresolvedProps {
+                    class: name.clone(),
+                    path: path.to_owned(),
+                })?,
+            }
+        }
+
+        let base = match builder.build_checked(ctx.view.clone(), ctx.strict) {
+            Ok(base) => base,
+            Err(e) => {
+                fail(BuildError::Use {
+                    class: name,
+                    error: Box::new(e),
+                })?;
+                continue;
+            },
+        };
+        *node.children_mut() = base.generated_html().children().to_all_owned();
+        let handle = ctx.view.register_component(Box::new(base));
+        components.insert(handle);
+    }
+    Ok(())
+}
+
+/// Parse HTML into the node which children can be edited.
+fn owned_node(html: &str) -> Option<Node> {
+    let mut node = Node::from_html(html, &Default::default()).ok()??;
+    let owned = node.children().to_all_owned();
+    *node.children_mut() = owned;
+    Some(node)
+}
+
+/// Evaluate conditional and repeated blocks in given children. Conditions remove elements
+/// whose property is false. Repeated blocks get their content copied for each item with
+/// IDs suffixed by the item index. Generated IDs are saved to `origins` along with
+/// the initial ID of the class.
+fn expand_directives(children: &mut Children, data: &Value,
+                     origins: &mut HashMap<String, String>) {
+    // Remove elements with false conditions.
+    let mut i = 0;
+    while i < children.len() {
+        let keep = if let Some(attr) = children.get(i).unwrap()
+                .attribute_by_name(template::IF_ATTR) {
+            template::condition(&attr.values_to_string(), data)
+        } else {
+            true
+        };
+
+        if keep {
+            i += 1;
+        } else {
+            children.remove(i);
+        }
+    }
+
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let repeat = node.attribute_by_name(template::FOR_ATTR)
+            .map(|attr| attr.values_to_string());
+        let repeat = if let Some(repeat) = repeat {
+            repeat
+        } else {
+            expand_directives(node.children_mut(), data, origins);
+            continue;
+        };
+
+        let (item_name, path) = template::parse_for(&repeat);
+        let items = template::lookup(data, path)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        // Content of the element is the template of one item.
+        let mut item_html = String::new();
+        for c in node.children().iter() {
+            item_html.push_str(&c.to_string());
+        }
+
+        let mut html = String::new();
+        for (index, item) in items.into_iter().enumerate() {
+            let scope = template::item_scope(data, item_name, item, index);
+            let s = template::interpolate_partial(&item_html, &scope);
+            let mut item_node = if let Some(node) = owned_node(&s) {
+                node
+            } else {
+                continue;
+            };
+
+            expand_directives(item_node.children_mut(), &scope, origins);
+            suffix_ids(item_node.children_mut(), &format!("-{}", index), origins);
+            html.push_str(&item_node.to_string());
+        }
+
+        if let Some(content) = owned_node(&html) {
+            *node.children_mut() = content.children().to_all_owned();
+        } else {
+            let children = node.children_mut();
+            while children.len() > 0 {
+                children.remove(0);
+            }
+        }
+    }
+}
+
+/// Append suffix to all IDs in given children and remember the initial IDs.
+fn suffix_ids(children: &mut Children, suffix: &str, origins: &mut HashMap<String, String>) {
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let id = node.attribute_by_name("id").map(|attr| attr.first_value().to_owned());
+        if let Some(id) = id {
+            let new_id = format!("{}{}", id, suffix);
+            let origin = origins.get(&id).cloned().unwrap_or(id);
+            origins.insert(new_id.clone(), origin);
+
+            let attr = Attribute::from_name_and_values(
+                "id".to_string(), vec![new_id]
+            ).unwrap();
+            node.overwrite_attribute(attr);
+        }
+
+        suffix_ids(node.children_mut(), suffix, origins);
+    }
+}
+
+/// Whether any of the nodes is an element with given tag.
+fn has_tag(children: &Children, tag: &str) -> bool {
+    children.iter().any(|child| {
+        let found = child.tag_name()
+            .map(|t| t.eq_ignore_ascii_case(tag))
+            .unwrap_or(false);
+        found || has_tag(child.children(), tag)
+    })
+}
+
+/// Remove script elements and collect their code.
+fn take_scripts(children: &mut Children, scripts: &mut Vec<String>) {
+    let mut i = 0;
+    while i < children.len() {
+        let is_script = children.get(i).unwrap().tag_name()
+            .map(|t| t.eq_ignore_ascii_case(SCRIPT_TAG))
+            .unwrap_or(false);
+        if is_script {
+            scripts.push(raw_text(children.get(i).unwrap()));
+            children.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    for child in children.iter_mut() {
+        if let Some(node) = child.try_mut() {
+            take_scripts(node.children_mut(), scripts);
+        }
+    }
+}
+
+/// Text of the script or style element as it is written in the source. Converting
+/// the node to a string would escape characters like `<` and `&`.
+fn raw_text(node: &Node) -> String {
+    node.children().iter()
+        .filter_map(|c| c.text())
+        .collect()
+}
+
+/// Remove style elements appending their content to the string and add the scope class
+/// to all other elements.
+fn take_styles(children: &mut Children, scope: &str, css: &mut String) {
+    let mut i = 0;
+    while i < children.len() {
+        let is_style = children.get(i).unwrap().tag_name()
+            .map(|t| t.eq_ignore_ascii_case(style::STYLE_TAG))
+            .unwrap_or(false);
+        if is_style {
+            css.push_str(&raw_text(children.get(i).unwrap()));
+            css.push('\n');
+            children.remove(i);
+        } else {
+            i += 1;
+        }
+    }
+
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+        if node.tag_name().is_none() {
+            continue;
         }
+
+        let mut values = node.attribute_by_name("class")
+            .map(|a| a.values().to_owned())
+            .unwrap_or_default();
+        values.push(scope.to_owned());
+        let attr = Attribute::from_name_and_values("class".to_string(), values).unwrap();
+        node.overwrite_attribute(attr);
+
+        take_styles(node.children_mut(), scope, css);
+    }
+}
+
+/// Replace the class of all elements that have it with another one.
+fn replace_class(children: &mut Children, old: &str, new: &str) {
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let values = node.attribute_by_name("class").map(|a| a.values().to_owned());
+        if let Some(values) = values {
+            if values.iter().any(|v| v == old) {
+                let values = values.into_iter()
+                    .map(|v| if v == old { new.to_owned() } else { v })
+                    .collect();
+                let attr = Attribute::from_name_and_values("class".to_string(), values).unwrap();
+                node.overwrite_attribute(attr);
+            }
+        }
+        replace_class(node.children_mut(), old, new);
+    }
+}
+
+/// Name of the slot if given node is a slot marker.
+fn slot_name(node: &Node) -> Option<String> {
+    let tag_name = node.tag_name()?;
+    if !tag_name.eq_ignore_ascii_case(SLOT_TAG) {
+        return None;
+    }
+
+    if let Some(attr) = node.attribute_by_name("name") {
+        Some(attr.first_value().to_owned())
+    } else {
+        Some(DEFAULT_SLOT.to_owned())
     }
 }
 
@@ -643,8 +1738,11 @@ impl Container for ComponentBase {
     fn remove_component(&mut self, component: &ComponentHandle) -> Option<()> {
         let found = self.components.remove(&component);
         if found {
-            let mut guard = self.view.inner.view.write().unwrap();
-            guard.remove_component(component);
+            {
+                let mut guard = self.view.inner.view.write().unwrap();
+                guard.remove_component(component);
+            }
+            self.view.teardown_component(component);
             Some(())
         } else {
             None
@@ -716,15 +1814,215 @@ impl Component for ComponentBase {
     fn class(&self) -> &ClassHandle {
         &self.class
     }
+
+    fn base(&self) -> Option<&ComponentBase> {
+        Some(self)
+    }
+
+    fn base_mut(&mut self) -> Option<&mut ComponentBase> {
+        Some(self)
+    }
+
+    fn release_elements(&mut self) {
+        for element in self.elements.values_mut() {
+            element.release();
+        }
+        if let Some(id) = self.form_callback.take() {
+            self.view.remove_callback(id);
+        }
+    }
+
+    fn run_scripts(&mut self) {
+        if self.scripts_run || self.class.scripts.is_empty() {
+            return;
+        }
+        self.scripts_run = true;
+
+        let ids: serde_json::Map<String, Value> = self.elements.iter()
+            .map(|(origin, e)| (origin.to_owned(), Value::String(e.id().to_owned())))
+            .collect();
+        let ids = Value::Object(ids);
+        for script in &self.class.scripts {
+            self.view.eval(format!("\
+                (function(root, ids) {{ {} }}).call(\
+                    document.getElementById('{}'), document.getElementById('{}'), {}\
+                );\
+            ", script, self.name(), self.name(), ids));
+        }
+    }
+
+    fn mounted_anchor(&self, component: &ComponentHandle) -> Option<(String, usize)> {
+        self.anchors.iter()
+            .filter_map(|(a, list)| list.iter().position(|h| h == component).map(|i| (a, i)))
+            .next()
+            .map(|(a, i)| (a.to_owned(), i))
+    }
+
+    fn query(&mut self, selector: &str) -> Result<Vec<Box<dyn Element>>, SelectorError> {
+        let selector = Selector::parse(selector)?;
+        let paths = match query::node_at(&self.html, &[0]) {
+            Some(element) => selector.select(element),
+            None => return Ok(Vec::new()),
+        };
+
+        let mut patches = Vec::new();
+        let mut elements = Vec::with_capacity(paths.len());
+        for path in paths {
+            let mut full = vec![0];
+            full.extend_from_slice(&path);
+            let node = query::node_at(&self.html, &full).unwrap();
+            let tag = TagName::try_from_node(node).unwrap();
+            let id = if let Some(id) = node.attribute_by_name("id") {
+                id.values_to_string()
+            } else {
+                let id = self.view.generate_id();
+                if let Some(node) = query::node_at_mut(&mut self.html, &full) {
+                    let attr = Attribute::from_name_and_values("id".to_owned(), vec![id.clone()]);
+                    node.overwrite_attribute(attr.unwrap());
+                }
+                patches.push(diff::Patch::SetAttribute {
+                    path,
+                    name: "id".to_owned(),
+                    value: id.clone(),
+                });
+                id
+            };
+            elements.push(tag.new_impl(self.view.clone(), id));
+        }
+
+        if !patches.is_empty() {
+            self.view.eval(diff::patches_js(self.name(), &patches));
+        }
+        Ok(elements)
+    }
 }
 
 impl ComponentBase {
 
+    /// Create the component of the class from generated HTML and the map of original IDs
+    /// of the elements to their generated IDs, as they were saved to the snapshot.
+    /// None is returned if HTML cannot be parsed or any of the elements is not found in it.
+    pub fn from_generated(view: ViewWrap, class: ClassHandle, html: &str,
+                          ids: &HashMap<String, String>) -> Option<Self> {
+        let html = owned_node(html)?;
+
+        let mut elements = HashMap::with_capacity(ids.len());
+        for (origin, id) in ids {
+            let node = html.children_fetch()
+                .key("id")
+                .value(id)
+                .fetch()
+                .into_iter()
+                .next()?;
+            let element = TagName::try_impl_from_node(node, view.clone())?;
+            elements.insert(origin.to_owned(), element);
+        }
+        if !elements.contains_key(class.name()) {
+            return None;
+        }
+
+        Some(ComponentBase {
+            view,
+            html,
+            elements,
+            components: Default::default(),
+            anchors: class.anchors.iter().map(|a| (a.to_owned(), Vec::new())).collect(),
+            form_callback: None,
+            scripts_run: false,
+            class,
+        })
+    }
+
+    /// Add the component which is not on the page yet as a sub-component that is shown
+    /// together with this component. If the component is mounted to the anchor it is expected
+    /// to be already present in generated HTML of this component and it becomes the last
+    /// component of that anchor. Otherwise, HTML of the component is added to the end of this
+    /// component if it is not there yet. Page is not changed.
+    pub fn adopt(&mut self, component: Box<dyn Component>, anchor: Option<&str>)
+            -> Result<ComponentHandle, MountError> {
+        if let Some(anchor) = anchor {
+            if !self.anchors.contains_key(anchor) {
+                return Err(MountError::UnknownAnchor);
+            }
+        }
+
+        let name = component.name().to_owned();
+        let present = self.html.children_fetch()
+            .key("id")
+            .value(&name)
+            .fetch()
+            .len() > 0;
+        if !present {
+            let html = component.generated_html().to_string();
+            for child in self.html.children_mut().iter_mut() {
+                let node = if let Some(node) = child.try_mut() {
+                    node
+                } else {
+                    continue;
+                };
+                if node.tag_name().is_none() {
+                    continue;
+                }
+
+                let mut parts: Vec<String> = node.children().iter()
+                    .map(|c| c.to_string())
+                    .collect();
+                parts.push(html);
+                if let Some(content) = owned_node(&parts.concat()) {
+                    *node.children_mut() = content.children().to_all_owned();
+                }
+                break;
+            }
+        }
+
+        let handle = self.add_component(component).map_err(|e| MountError::Add(e))?;
+        if let Some(anchor) = anchor {
+            self.anchors.get_mut(anchor).unwrap().push(handle.clone());
+        }
+        Ok(handle)
+    }
+
+    /// Remove the element with given ID and it's content from generated HTML of this
+    /// component. Page is not changed. Get whether the element was found.
+    pub fn remove_html(&mut self, id: &str) -> bool {
+        fn remove(children: &mut Children, id: &str) -> bool {
+            let pos = children.iter().position(|c| {
+                c.attribute_by_name("id").map(|a| a.first_value() == id).unwrap_or(false)
+            });
+            if let Some(pos) = pos {
+                children.remove(pos);
+                return true;
+            }
+            children.iter_mut()
+                .filter_map(|c| c.try_mut())
+                .any(|node| remove(node.children_mut(), id))
+        }
+
+        remove(self.html.children_mut(), id)
+    }
+
+    /// Translate text of the elements which are marked with translation keys in generated
+    /// HTML using the current locale of the view. Page is not changed.
+    pub fn translate_html(&mut self) {
+        let guard = self.view.inner.view.read().unwrap();
+        translate_node(&mut self.html, &guard.translations);
+    }
+
     /// Current mutable HTML code of the component.
     pub fn current_html_mut(&mut self) -> &mut Node {
         &mut self.html
     }
 
+    /// Get element by original ID if it has given type.
+    pub fn element<T: Element + 'static>(&self, id: &str) -> Option<&T> {
+        self.elements.get(id).and_then(|e| e.downcast_ref())
+    }
+
+    /// Get mutable element by original ID if it has given type.
+    pub fn element_mut<T: Element + 'static>(&mut self, id: &str) -> Option<&mut T> {
+        self.elements.get_mut(id).and_then(|e| e.downcast_mut())
+    }
+
     /// Get element by original ID.
     pub fn element_by_origin_mut(&mut self, id: &str) -> Option<&mut Box<dyn Element>> {
         self.elements.get_mut(id)
@@ -734,6 +2032,437 @@ impl ComponentBase {
     pub fn elements_mut(&mut self) -> &mut HashMap<String, Box<dyn Element>> {
         &mut self.elements
     }
+
+    /// Bind the observable to the elements of this component which refer to it by given
+    /// name in `BIND_ATTR` attribute. Get number of created bindings.
+    pub fn bind<T: serde::Serialize>(&self, name: &str, value: &Observable<T>) -> usize {
+        let root = self.name();
+        let fetch = self.html.children_fetch()
+            .key(observable::BIND_ATTR);
+
+        let mut count = 0;
+        let mut seen = HashSet::new();
+        for node in fetch.fetch() {
+            // Selector matches all elements with the same attribute value at once.
+            let attr = node.attribute_by_name(observable::BIND_ATTR).unwrap().values_to_string();
+            if !seen.insert(attr.clone()) {
+                continue;
+            }
+            for (kind, bound) in observable::parse_bindings(&attr) {
+                if bound != name {
+                    continue;
+                }
+
+                let attr_json = serde_json::to_string(&attr).unwrap();
+                let selector = format!("#{root}[{bind}={value}], #{root} [{bind}={value}]",
+                    root = root, bind = observable::BIND_ATTR, value = attr_json);
+                value.bind(Binding::new(selector, kind));
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Bring the component to the state of given HTML. New HTML is compared with generated
+    /// HTML of this component and only changed attributes, texts and children are updated
+    /// on the page, so focus and scroll of untouched elements are kept. Node can be either
+    /// the root of parsed HTML or the component element itself and it must keep the ID of
+    /// the component. Element bindings of removed or replaced elements are not updated.
+    /// Sub-components stay where they are unless the element that holds them is replaced,
+    /// in which case they are torn down. Elements that were removed are released and new
+    /// elements with IDs are registered by their IDs. Get applied patches.
+    pub fn update_html(&mut self, html: &Node) -> Vec<diff::Patch> {
+        let patches = {
+            let old = diff::element(&self.html);
+            let new = diff::element(html);
+            match (old, new) {
+                (Some(old), Some(new)) => diff::diff(old, new),
+                _ => return Vec::new(),
+            }
+        };
+        if patches.is_empty() {
+            return patches;
+        }
+
+        self.view.eval(diff::patches_js(self.name(), &patches));
+        if let Some(mut node) = owned_node(&diff::element(html).unwrap().to_string()) {
+            if let Some(new) = query::node_at_mut(&mut node, &[0]) {
+                diff::keep_nested(diff::element(&self.html).unwrap(), new);
+            }
+            let mut old_ids = HashSet::new();
+            collect_ids(self.html.children(), &mut old_ids);
+            self.html = node;
+            self.sync_with_html(&old_ids);
+        }
+        patches
+    }
+
+    /// Forget sub-components and elements which were removed from generated HTML and
+    /// register elements that appeared in it. Removed sub-components are torn down.
+    /// Only sub-components that were in the HTML with given IDs are checked, so
+    /// sub-components that were never rendered into this component are kept.
+    fn sync_with_html(&mut self, old_ids: &HashSet<String>) {
+        let mut ids = HashSet::new();
+        collect_ids(self.html.children(), &mut ids);
+
+        let removed: Vec<ComponentHandle> = self.components.iter()
+            .filter(|c| {
+                let guard = c.read();
+                old_ids.contains(guard.name()) && !ids.contains(guard.name())
+            })
+            .cloned()
+            .collect();
+        for component in removed {
+            for list in self.anchors.values_mut() {
+                list.retain(|h| h != &component);
+            }
+            self.remove_component(&component);
+        }
+
+        let removed: Vec<String> = self.elements.iter()
+            .filter(|(_, e)| !e.id().is_empty() && !ids.contains(e.id()))
+            .map(|(origin, _)| origin.to_owned())
+            .collect();
+        for origin in removed {
+            self.elements.remove(&origin).unwrap().release();
+        }
+
+        let mut added = Vec::new();
+        new_elements(self.html.children(), old_ids, true, &mut added);
+        for node in added {
+            if let Some(element) = TagName::try_impl_from_node(node, self.view.clone()) {
+                self.elements.insert(element.id().to_owned(), element);
+            }
+        }
+    }
+
+    /// Map of original IDs of the elements to their generated IDs. Root element is skipped.
+    fn form_ids(&self) -> Value {
+        let root = self.class.name();
+        let map = self.elements.iter()
+            .filter(|(origin, _)| *origin != root)
+            .map(|(origin, element)| (origin.to_owned(), Value::String(element.id().to_owned())))
+            .collect();
+        Value::Object(map)
+    }
+
+    /// Read all form controls of this component in one round trip into the struct. Controls
+    /// are matched to the fields by original placeholder ID or by `name` attribute.
+    pub fn form_value<T: DeserializeOwned>(&self) -> Result<T, FormError> {
+        let req = self.view.clone().new_request().owner(self.name().to_owned());
+        let js = form::read_js(self.name(), &self.form_ids(), req.id());
+        let response = req.run(js).recv().map_err(|_| FormError::NoResponse)?;
+
+        if let ResponseValue::Str(s) = response {
+            serde_json::from_str(&s).map_err(|e| FormError::Deserialize(e))
+        } else {
+            unreachable!()
+        }
+    }
+
+    /// Write fields of the struct into form controls of this component. Controls
+    /// are matched to the fields by original placeholder ID or by `name` attribute.
+    pub fn set_form_value<T: serde::Serialize>(&self, value: &T) -> Result<(), FormError> {
+        let value = serde_json::to_value(value).map_err(|e| FormError::Serialize(e))?;
+        if !value.is_object() {
+            return Err(FormError::NotAnObject);
+        }
+        self.view.eval_batched(form::write_js(self.name(), &self.form_ids(), &value));
+        Ok(())
+    }
+
+    /// Set callback that is called each time the user changes any form control of this
+    /// component. Arguments of the callback can be parsed with `FormChange::from_args`.
+    /// Previous callback is replaced.
+    pub fn on_form_change(&mut self, callback: Box<&'static Callback>) {
+        if let Some(id) = self.form_callback.take() {
+            self.view.remove_callback(id);
+        }
+        let owner = self.name().to_owned();
+        let id = self.view.add_callback(callback, &owner);
+        self.form_callback = Some(id);
+        self.view.eval(form::listen_js(&owner, &self.form_ids(), id));
+    }
+
+    /// Stop notifying about changes of the form controls.
+    pub fn remove_form_change(&mut self) {
+        if let Some(id) = self.form_callback.take() {
+            self.view.eval(form::unlisten_js(self.name()));
+            self.view.remove_callback(id);
+        }
+    }
+
+    /// Components mounted to given anchor in the order they appear in HTML.
+    pub fn mounted(&self, anchor: &str) -> Option<&Vec<ComponentHandle>> {
+        self.anchors.get(anchor)
+    }
+
+    /// Add the component to this container and insert it's HTML to the anchor.
+    /// Both the page and generated HTML of this component get updated.
+    pub fn mount(&mut self, anchor: &str, component: Box<dyn Component>,
+                 position: MountPosition) -> Result<ComponentHandle, MountError> {
+        let (index, sibling) = self.mount_place(anchor, &position)?;
+
+        let html = component.generated_html().to_string();
+        let handle = self.add_component(component).map_err(|e| MountError::Add(e))?;
+        self.anchors.get_mut(anchor).unwrap().insert(index, handle.clone());
+
+        let html_js = serde_json::to_string(&html).unwrap();
+        let js = self.insert_js(anchor, &position, sibling.as_ref(), "insertAdjacentHTML",
+            &html_js);
+        self.view.eval(js);
+        self.insert_mirror(anchor, &position, sibling.as_ref(), html);
+
+        handle.mounted();
+        Ok(handle)
+    }
+
+    /// Move the component mounted to any of the anchors to new position in the same anchor.
+    /// Component is not rebuilt so it keeps it's state and the state of the page elements.
+    pub fn move_mounted(&mut self, component: &ComponentHandle, position: MountPosition)
+            -> Result<(), MountError> {
+        let anchor = self.anchors.iter()
+            .find(|(_, list)| list.contains(component))
+            .map(|(a, _)| a.to_owned())
+            .ok_or(MountError::NotMounted)?;
+        if let MountPosition::Before(s) | MountPosition::After(s) = &position {
+            if s == component {
+                return Ok(());
+            }
+        }
+
+        let old = {
+            let list = self.anchors.get_mut(&anchor).unwrap();
+            let old = list.iter().position(|h| h == component).unwrap();
+            list.remove(old);
+            old
+        };
+        let (index, sibling) = match self.mount_place(&anchor, &position) {
+            Ok(place) => place,
+            Err(e) => {
+                self.anchors.get_mut(&anchor).unwrap().insert(old, component.clone());
+                return Err(e);
+            },
+        };
+        self.anchors.get_mut(&anchor).unwrap().insert(index, component.clone());
+
+        let name = component.read().name().to_owned();
+        let js = self.insert_js(&anchor, &position, sibling.as_ref(), "insertAdjacentElement",
+            &format!("document.getElementById('{}')", name));
+        self.view.eval(js);
+
+        let html = {
+            let node = anchor_node_mut(self.html.children_mut(), &anchor, true).unwrap();
+            let pos = node.children().iter().position(|c| {
+                c.attribute_by_name("id").map(|a| a.first_value() == &name).unwrap_or(false)
+            });
+            pos.map(|pos| {
+                let html = node.children().get(pos).unwrap().to_string();
+                node.children_mut().remove(pos);
+                html
+            })
+        };
+        if let Some(html) = html {
+            self.insert_mirror(&anchor, &position, sibling.as_ref(), html);
+        }
+        Ok(())
+    }
+
+    /// Index in the list of mounted components of the anchor and the name of the sibling
+    /// for given position.
+    fn mount_place(&self, anchor: &str, position: &MountPosition)
+            -> Result<(usize, Option<String>), MountError> {
+        let list = self.anchors.get(anchor).ok_or(MountError::UnknownAnchor)?;
+        let find = |sibling: &ComponentHandle| {
+            list.iter().position(|h| h == sibling).ok_or(MountError::UnknownSibling)
+        };
+        Ok(match position {
+            MountPosition::Append => (list.len(), None),
+            MountPosition::Prepend => (0, None),
+            MountPosition::Before(s) => (find(s)?, Some(s.read().name().to_owned())),
+            MountPosition::After(s) => (find(s)? + 1, Some(s.read().name().to_owned())),
+        })
+    }
+
+    /// JS code that puts the content to the position in the anchor with given
+    /// `insertAdjacent*` function.
+    fn insert_js(&self, anchor: &str, position: &MountPosition, sibling: Option<&String>,
+                 function: &str, content: &str) -> String {
+        match position {
+            MountPosition::Append | MountPosition::Prepend => {
+                let place = if let MountPosition::Append = position {
+                    "beforeend"
+                } else {
+                    "afterbegin"
+                };
+                format!("{}a.{}('{}', {});",
+                    anchor_js(self.name(), anchor), function, place, content)
+            },
+            MountPosition::Before(_) | MountPosition::After(_) => {
+                let place = if let MountPosition::Before(_) = position {
+                    "beforebegin"
+                } else {
+                    "afterend"
+                };
+                format!("document.getElementById('{}').{}('{}', {});",
+                    sibling.unwrap(), function, place, content)
+            },
+        }
+    }
+
+    /// Put HTML of the mounted component to the position in the anchor of generated HTML.
+    fn insert_mirror(&mut self, anchor: &str, position: &MountPosition,
+                     sibling: Option<&String>, html: String) {
+        let node = anchor_node_mut(self.html.children_mut(), anchor, true).unwrap();
+        let mut parts: Vec<String> = node.children().iter().map(|c| c.to_string()).collect();
+        let pos = match position {
+            MountPosition::Append => parts.len(),
+            MountPosition::Prepend => 0,
+            MountPosition::Before(_) | MountPosition::After(_) => {
+                let sibling = sibling.unwrap();
+                let pos = node.children().iter().position(|c| {
+                    c.attribute_by_name("id").map(|a| a.first_value() == sibling).unwrap_or(false)
+                }).unwrap_or(parts.len());
+                if let MountPosition::After(_) = position {
+                    pos + 1
+                } else {
+                    pos
+                }
+            },
+        };
+        parts.insert(pos.min(parts.len()), html);
+        if let Some(content) = owned_node(&parts.concat()) {
+            *node.children_mut() = content.children().to_all_owned();
+        }
+    }
+
+    /// Remove the component mounted to any of the anchors from this container, the page
+    /// and generated HTML of this component.
+    pub fn unmount(&mut self, component: &ComponentHandle) -> Option<()> {
+        let anchor = self.anchors.iter()
+            .find(|(_, list)| list.contains(component))
+            .map(|(a, _)| a.to_owned())?;
+        self.anchors.get_mut(&anchor).unwrap().retain(|h| h != component);
+
+        // Component is removed from the interface while it still is on the page so
+        // unmount hooks can access it.
+        let name = component.read().name().to_owned();
+        self.remove_component(component)?;
+        self.view.eval(format!("document.getElementById('{}').outerHTML = '';", name));
+
+        if let Some(node) = anchor_node_mut(self.html.children_mut(), &anchor, true) {
+            let pos = node.children().iter().position(|c| {
+                c.attribute_by_name("id").map(|a| a.first_value() == &name).unwrap_or(false)
+            });
+            if let Some(pos) = pos {
+                node.children_mut().remove(pos);
+            }
+        }
+
+        Some(())
+    }
+}
+
+/// JS code that finds the anchor of the component and saves it to variable `a`.
+/// Anchors of nested components with the same name are ignored.
+fn anchor_js(component: &str, anchor: &str) -> String {
+    format!("\
+        var r = document.getElementById('{id}');\
+        var a = r.getAttribute('{attr}') == '{anchor}' ? r : null;\
+        if (a == null) {{\
+            var list = r.querySelectorAll('[{attr}=\"{anchor}\"]');\
+            for (var i = 0; i < list.length; i++) {{\
+                if (list[i].closest('.{mark}') === r) {{\
+                    a = list[i];\
+                    break;\
+                }}\
+            }}\
+        }}\
+    ", id = component, attr = ANCHOR_ATTR, anchor = anchor, mark = COMPONENT_MARK)
+}
+
+/// Translate text of the elements which are marked with translation keys. Elements
+/// which keys have no translation are not changed.
+fn translate_node(html: &mut Node, translations: &Translations) {
+    let mut fetch = html.children_fetch_mut()
+        .key(i18n::I18N_KEY_ATTR)
+        .fetch_mut();
+    for node in fetch.iter_mut() {
+        if let NodeAccess::Owned(ref mut node) = node {
+            let key = node.attribute_by_name(i18n::I18N_KEY_ATTR).unwrap()
+                .first_value().to_owned();
+            let args = if let Some(attr) = node.attribute_by_name(i18n::I18N_ARGS_ATTR) {
+                i18n::args_from_json(&attr.values_to_string())
+            } else {
+                Default::default()
+            };
+
+            if let Some(text) = translations.translate(&key, &args) {
+                node.set_text(text);
+            }
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+/// Add IDs of all elements including the ones of nested components to the set.
+fn collect_ids(children: &Children, set: &mut HashSet<String>) {
+    for child in children.iter() {
+        if let Some(attr) = child.attribute_by_name("id") {
+            set.insert(attr.first_value().to_owned());
+        }
+        collect_ids(child.children(), set);
+    }
+}
+
+/// Find elements with IDs which are not in the set. Nested components are not searched.
+fn new_elements<'a>(children: &'a Children, ids: &HashSet<String>, top: bool,
+                    vec: &mut Vec<&'a Node>) {
+    for child in children.iter() {
+        let nested = !top && child.attribute_by_name("class")
+            .map(|a| a.values().contains(&COMPONENT_MARK.to_string()))
+            .unwrap_or(false);
+        if nested {
+            continue;
+        }
+        if let Some(attr) = child.attribute_by_name("id") {
+            if !ids.contains(attr.first_value()) {
+                vec.push(&**child);
+            }
+        }
+        new_elements(child.children(), ids, false, vec);
+    }
+}
+
+/// Find node of the anchor with given name. Nested components are not searched.
+fn anchor_node_mut<'a>(children: &'a mut Children, anchor: &str, top: bool)
+        -> Option<&'a mut Node> {
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let is_anchor = node.attribute_by_name(ANCHOR_ATTR)
+            .map(|a| a.first_value() == anchor)
+            .unwrap_or(false);
+        if is_anchor {
+            return Some(node);
+        }
+
+        let nested = !top && node.attribute_by_name("class")
+            .map(|a| a.values().contains(&COMPONENT_MARK.to_string()))
+            .unwrap_or(false);
+        if nested {
+            continue;
+        }
+        if let Some(found) = anchor_node_mut(node.children_mut(), anchor, false) {
+            return Some(found);
+        }
+    }
+    None
 }
 
 impl ComponentHandle {
@@ -771,6 +2500,62 @@ impl ComponentHandle {
         self.id
     }
 
+    /// Emit custom event from this component. See `ViewWrap::emit`.
+    pub fn emit<E: 'static>(&self, data: E) -> ComponentEvent<E> {
+        self.view.emit(self, data)
+    }
+
+    /// Handle custom events of type `E` emitted by this component or any of it's
+    /// sub-components. See `ViewWrap::on_event`.
+    pub fn on_event<E, F>(&self, handler: F) -> HandlerId
+            where E: 'static, F: Fn(&ViewWrap, &mut ComponentEvent<E>) + Send + Sync + 'static {
+        self.view.on_event(self, handler)
+    }
+
+    /// Lock of the component as it is stored in the interface.
+    pub fn lock(&self) -> &Arc<RwLock<Box<dyn Component>>> {
+        &self.lock
+    }
+
+    /// Run mount hooks of the component and all of it's sub-components. Containers
+    /// call this after they insert HTML of the component into the page.
+    pub fn mounted(&self) {
+        let children: Vec<ComponentHandle> = {
+            let mut guard = self.lock.write().unwrap();
+            guard.run_scripts();
+            guard.on_mount();
+            guard.components().iter().cloned().collect()
+        };
+        for child in children {
+            child.mounted();
+        }
+    }
+
+    /// Show or hide the component on the page. Show and hide hooks of the component
+    /// and all of it's sub-components are called.
+    pub fn set_visible(&self, visible: bool) {
+        let name = self.read().name().to_owned();
+        self.view.eval(format!(
+            "document.getElementById('{}').hidden = {};", name, !visible
+        ));
+        self.visibility_changed(visible);
+    }
+
+    fn visibility_changed(&self, visible: bool) {
+        let children: Vec<ComponentHandle> = {
+            let mut guard = self.lock.write().unwrap();
+            if visible {
+                guard.on_show();
+            } else {
+                guard.on_hide();
+            }
+            guard.components().iter().cloned().collect()
+        };
+        for child in children {
+            child.visibility_changed(visible);
+        }
+    }
+
     pub fn read(&self) -> RwLockReadGuardRef<Box<dyn Component>> {
         RwLockReadGuardRef::new(self.lock.read().unwrap())
     }
@@ -802,6 +2587,7 @@ impl<T> ComponentHandleT<T>
 
     /// Create new handle with type for given raw handle. This is up to programmer
     /// to ensure the type of passed component is correct.
+    /// Consider using `try_new` which checks the type.
     pub unsafe fn new(handle: ComponentHandle) -> Self {
         ComponentHandleT {
             handle,
@@ -810,35 +2596,59 @@ impl<T> ComponentHandleT<T>
     }
 }
 
-impl<T> Deref for ComponentHandleT<T>
-    where T: Component {
+impl<T> ComponentHandleT<T>
+    where T: Component + 'static
+{
 
-    type Target = Arc<RwLock<Box<T>>>;
+    /// Create new handle with type if the component of raw handle has this type.
+    /// Otherwise raw handle is given back.
+    pub fn try_new(handle: ComponentHandle) -> Result<Self, ComponentHandle> {
+        let is = {
+            let guard = handle.read();
+            guard.is::<T>()
+        };
+        if is {
+            Ok(unsafe { Self::new(handle) })
+        } else {
+            Err(handle)
+        }
+    }
 
-    fn deref(&self) -> &Self::Target {
-        let lock = &self.handle.lock;
-        let ptr = lock as *const Arc<RwLock<Box<dyn Component>>>;
-        let ptr = ptr as *const Self::Target;
-        unsafe { &*ptr }
+    /// Get the raw handle.
+    pub fn handle(&self) -> &ComponentHandle {
+        &self.handle
     }
-}
 
-impl<T> DerefMut for ComponentHandleT<T>
-    where T: Component {
+    /// Lock the component for reading.
+    ///
+    /// # Panics
+    /// Handle that was created with `new` for a component of other type panics.
+    pub fn read(&self) -> RwLockReadGuardRef<Box<dyn Component>, T> {
+        self.handle.read().map(|c| c.downcast_ref::<T>().expect(WRONG_TYPE))
+    }
 
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        let lock = &mut self.handle.lock;
-        let ptr = lock as *mut Arc<RwLock<Box<dyn Component>>>;
-        let ptr = ptr as *mut Self::Target;
-        unsafe { &mut *ptr }
+    /// Lock the component for writing.
+    ///
+    /// # Panics
+    /// Handle that was created with `new` for a component of other type panics.
+    pub fn write(&mut self) -> RwLockWriteGuardRefMut<Box<dyn Component>, T> {
+        self.handle.write().map_mut(|c| c.downcast_mut::<T>().expect(WRONG_TYPE))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
     use typed_html::dom::DOMTree;
-    use crate::component::{Class};
-    use crate::component::{COMPONENT_MARK, SKIP_ELEMENT_MARK};
+    use crate::component::{BuildError, Class, Component, ComponentBase, ComponentHandleT,
+        InstanceBuilder, MountPosition};
+    use crate::diff::Patch;
+    use htmldom_read::Node;
+    use crate::component::{COMPONENT_MARK, SKIP_ELEMENT_MARK, DEFAULT_SLOT};
+    use crate::tags::{P, Span, Wrap};
+    use crate::{RootComponent, View};
+    use crate::registry::ClassRegistry;
+    use crate::template::PropsError;
     use typed_html::types::Id;
 
     #[test]
@@ -898,4 +2708,381 @@ mod tests {
         assert!(map.contains_key("some"));
         assert!(!map.contains_key("other"));
     }
+
+    #[test]
+    fn class_slots() {
+        // Slot tag is not known to `html!` macro.
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <slot name=\"header\"></slot>\
+                <p><slot>Default content</slot></p>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let class = Class::try_from_html(&html).unwrap();
+        assert!(class.slots().contains("header"));
+        assert!(class.slots().contains(DEFAULT_SLOT));
+        assert_eq!(class.slots().len(), 2);
+    }
+
+    #[test]
+    fn filled_slot_elements() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <slot name=\"header\"><p id=\"defaultTitle\">Default</p></slot>\
+                <slot><span id=\"defaultBody\"></span></slot>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut builder = Class::try_from_html(&html).unwrap().into_builder();
+        builder.fill_slot_html("header", "<b>Title</b>").unwrap();
+        builder.use_view_names(&view);
+        let base = builder.build(view);
+
+        assert!(base.elements().get("defaultTitle").is_none());
+        assert!(base.elements().get("defaultBody").is_some());
+        let generated = base.generated_html().to_string();
+        assert!(generated.contains("<b>Title</b>"));
+        assert!(!generated.contains("Default"));
+    }
+
+    #[test]
+    fn class_scripts_and_styles() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <style>p {{ color: red; }}</style>\
+                <p id=\"title\"></p>\
+                <script>root.hidden = false;</script>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let class = Class::try_from_html(&html).unwrap();
+        assert_eq!(class.scripts(), &vec!["root.hidden = false;".to_string()]);
+        assert_eq!(class.style().unwrap(), "p.uitacoScope-card{color: red;}");
+        assert!(class.placeholders().contains_key("title"));
+    }
+
+    #[test]
+    fn class_scripts_raw_text() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <style>div > p {{ color: red; }}</style>\
+                <script>if (a < b && b > 0) {{ root.title = '&amp;'; }}</script>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let class = Class::try_from_html(&html).unwrap();
+        assert_eq!(class.scripts(),
+            &vec!["if (a < b && b > 0) { root.title = '&amp;'; }".to_string()]);
+        assert!(class.style().unwrap().contains('>'));
+    }
+
+    #[test]
+    fn class_uses() {
+        let html: DOMTree<String> = html!(
+            <body>
+                <div class=COMPONENT_MARK id="userCard">
+                    <p id="userName"></p>
+                </div>
+                <div class=COMPONENT_MARK id="userList">
+                    <div data-uitaco-use="userCard" data-uitaco-props="first"></div>
+                    <div data-uitaco-use="userCard"></div>
+                </div>
+            </body>
+        );
+        let html = html.to_string();
+
+        let map = Class::all_from_html(&html);
+        let list = map.get("userList").unwrap();
+        assert_eq!(list.used_classes().len(), 1);
+        assert!(list.used_classes().contains("userCard"));
+        assert!(map.get("userCard").unwrap().used_classes().is_empty());
+    }
+
+    #[test]
+    fn namespaced_self_reference() {
+        let html = format!("\
+            <div class=\"{}\" id=\"tree\">\
+                <div data-uitaco-use=\"ui::tree\"></div>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let mut registry = ClassRegistry::new();
+        registry.load_html(Some("ui"), &html).unwrap();
+        let view = View::new_headless();
+        match registry.builder("ui::tree").unwrap().try_build(view.clone()) {
+            Err(BuildError::Cycle(name)) => assert_eq!(name, "ui::tree"),
+            _ => panic!("class was built inside of itself"),
+        }
+        let base = registry.builder("ui::tree").unwrap().build(view);
+        assert!(base.components().is_empty());
+    }
+
+    #[test]
+    fn broken_references() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"card\"><p id=\"title\">{{{{title}}}}</p></div>\
+            <div class=\"{mark}\" id=\"missing\">\
+                <div data-uitaco-use=\"nothing\"></div>\
+            </div>\
+            <div class=\"{mark}\" id=\"unresolved\">\
+                <div data-uitaco-use=\"card\" data-uitaco-props=\"first\"></div>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let mut registry = ClassRegistry::new();
+        registry.load_html(None, &html).unwrap();
+        let view = View::new_headless();
+        match registry.builder("missing").unwrap().try_build(view.clone()) {
+            Err(BuildError::UnknownClass(name)) => assert_eq!(name, "nothing"),
+            _ => panic!("unknown class was not reported"),
+        }
+        match registry.builder("unresolved").unwrap().try_build(view.clone()) {
+            Err(BuildError::UnresolvedProps { class, path }) =>
+                assert_eq!((class.as_str(), path.as_str()), ("card", "first")),
+            _ => panic!("unresolved properties were not reported"),
+        }
+
+        let mut props = HashMap::new();
+        props.insert("first", HashMap::<&str, &str>::new());
+        let builder = registry.builder("unresolved").unwrap().with_props(&props).unwrap();
+        match builder.try_build(view) {
+            Err(BuildError::Use { class, error }) => {
+                assert_eq!(class, "card");
+                match *error {
+                    BuildError::Props(PropsError::Missing(path)) => assert_eq!(path, "title"),
+                    _ => panic!("missing property was not reported"),
+                }
+            },
+            _ => panic!("reference without required properties was built"),
+        }
+    }
+
+    #[test]
+    fn namespaced_styles() {
+        let html = format!("\
+            <div class=\"{}\" id=\"item\">\
+                <style>p {{ color: red; }}</style>\
+                <p id=\"title\"></p>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let mut registry = ClassRegistry::new();
+        registry.load_html(Some("forms"), &html).unwrap();
+        registry.load_html(Some("menu"), &html).unwrap();
+        let forms = registry.get("forms::item").unwrap();
+        let menu = registry.get("menu::item").unwrap();
+        assert_eq!(forms.style().unwrap(), "p.uitacoScope-forms--item{color: red;}");
+        assert_eq!(menu.style().unwrap(), "p.uitacoScope-menu--item{color: red;}");
+
+        let view = View::new_headless();
+        let base = registry.builder("menu::item").unwrap().build(view.clone());
+        assert!(base.generated_html().to_string().contains("uitacoScope-menu--item"));
+        registry.builder("forms::item").unwrap().build(view.clone());
+        assert_eq!(view.injected_css().len(), 2);
+    }
+
+    #[test]
+    fn component_query() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"badge\">\
+                <span class=\"t\"></span>\
+            </div>\
+            <div class=\"{mark}\" id=\"card\">\
+                <p id=\"title\" class=\"t\"></p>\
+                <div data-uitaco-use=\"badge\"></div>\
+                <span class=\"t\"></span>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let badge = classes.remove("badge").unwrap().into_handle();
+        let mut builder = classes.remove("card").unwrap().into_builder();
+        builder.use_class(badge);
+        builder.use_view_names(&view);
+        let mut base = builder.build(view);
+        assert_eq!(base.components().len(), 1);
+
+        // Span of the badge is not found.
+        let found = base.query(".t").unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].id(), base.elements().get("title").unwrap().id());
+        assert_eq!(found[1].tag_name().name(), "span");
+
+        let generated = found[1].id().to_owned();
+        assert!(!generated.is_empty());
+        assert!(base.generated_html().to_string().contains(&generated));
+        assert_eq!(base.query("p ~ span").unwrap()[0].id(), &generated);
+        assert!(base.query("span:hover").is_err());
+    }
+
+    #[test]
+    fn update_keeps_mounted() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"badge\"></div>\
+            <div class=\"{mark}\" id=\"card\">\
+                <p id=\"title\">Old</p>\
+                <ul data-uitaco-anchor=\"items\"></ul>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let mut badge = classes.remove("badge").unwrap().into_builder();
+        badge.use_view_names(&view);
+        let mut card = classes.remove("card").unwrap().into_builder();
+        card.use_view_names(&view);
+        let mut base = card.build(view.clone());
+        let badge = Box::new(badge.build(view));
+        let item = base.mount("items", badge, MountPosition::Append).unwrap();
+        let item_name = item.read().name().to_owned();
+
+        let new = format!("<div class=\"{}\" id=\"{}\"><p id=\"{}\">New</p>\
+            <ul data-uitaco-anchor=\"items\"></ul></div>",
+            COMPONENT_MARK, base.name(), base.elements().get("title").unwrap().id());
+        let new = Node::from_html(&new, &Default::default()).unwrap().unwrap();
+        assert_eq!(base.update_html(&new), vec![Patch::SetText {
+            path: vec![0],
+            text: "New".to_string(),
+        }]);
+
+        let generated = base.generated_html().to_string();
+        assert!(generated.contains(">New<"));
+        assert!(generated.contains(&item_name));
+        assert_eq!(base.mounted("items").unwrap(), &vec![item]);
+    }
+
+    #[test]
+    fn update_replaces_mounted() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"badge\"></div>\
+            <div class=\"{mark}\" id=\"card\">\
+                <div id=\"box\"><ul data-uitaco-anchor=\"items\"></ul></div>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let mut badge = classes.remove("badge").unwrap().into_builder();
+        badge.use_view_names(&view);
+        let mut card = classes.remove("card").unwrap().into_builder();
+        card.use_view_names(&view);
+        let mut base = card.build(view.clone());
+        let badge = Box::new(badge.build(view));
+        let item = base.mount("items", badge, MountPosition::Append).unwrap();
+        let item_name = item.read().name().to_owned();
+
+        let box_id = base.elements().get("box").unwrap().id().to_owned();
+        let new = format!("<div class=\"{}\" id=\"{}\">\
+            <section id=\"{}\"><p id=\"fresh\">New</p></section></div>",
+            COMPONENT_MARK, base.name(), box_id);
+        let new = Node::from_html(&new, &Default::default()).unwrap().unwrap();
+        match &base.update_html(&new)[..] {
+            [Patch::Replace { path, .. }] => assert_eq!(path, &vec![0]),
+            _ => panic!("element was not replaced"),
+        }
+
+        assert!(!base.generated_html().to_string().contains(&item_name));
+        assert!(base.components().is_empty());
+        assert!(base.mounted("items").unwrap().is_empty());
+        assert!(base.elements().contains_key("box"));
+        assert!(base.elements().contains_key("fresh"));
+    }
+
+    #[test]
+    fn mount_through_trait() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"badge\"></div>\
+            <div class=\"{mark}\" id=\"card\">\
+                <ul data-uitaco-anchor=\"items\"></ul>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let mut badge = classes.remove("badge").unwrap().into_builder();
+        badge.use_view_names(&view);
+        let badge = Box::new(badge.build(view.clone()));
+        let mut card = classes.remove("card").unwrap().into_builder();
+        card.use_view_names(&view);
+        let mut card = card.build(view);
+
+        let parent: &mut dyn Component = &mut card;
+        let item = parent.mount("items", badge, MountPosition::Prepend).unwrap();
+        assert_eq!(parent.mounted("items").unwrap(), &vec![item.clone()]);
+        assert!(parent.unmount(&item).is_some());
+        assert!(parent.mounted("items").unwrap().is_empty());
+    }
+
+    #[test]
+    fn required_props() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <p id=\"title\">{{{{title}}}}{{{{subtitle?}}}}</p>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let class = Class::try_from_html(&html).unwrap().into_handle();
+        match InstanceBuilder::new_for_handle(class.clone()).try_build(view.clone()) {
+            Err(BuildError::Props(PropsError::Missing(path))) => assert_eq!(path, "title"),
+            _ => panic!("instance was built without required property"),
+        }
+
+        // Unchecked build leaves the text as is when no properties were given.
+        let base = InstanceBuilder::new_for_handle(class.clone()).build(view.clone());
+        assert!(base.generated_html().to_string().contains("{{title}}"));
+
+        let mut props = HashMap::new();
+        props.insert("title", "Hello");
+        let builder = InstanceBuilder::new_for_handle(class).with_props(&props).unwrap();
+        let base = builder.try_build(view).unwrap();
+        assert!(base.generated_html().to_string().contains(">Hello<"));
+    }
+
+    #[test]
+    fn checked_elements() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <p id=\"title\"></p>\
+                <span id=\"note\"></span>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let class = Class::try_from_html(&html).unwrap();
+        let mut base = class.into_builder().build(view);
+        assert!(base.element::<P>("title").is_some());
+        assert!(base.element::<Span>("title").is_none());
+        assert!(base.element_mut::<Span>("note").is_some());
+        assert!(base.element::<P>("missing").is_none());
+
+        let title = base.elements().get("title").unwrap();
+        assert!(title.is::<P>() && !title.is::<Span>());
+        assert!(title.downcast_ref::<P>().is_some());
+
+        let note_id = base.elements().get("note").unwrap().id().to_string();
+        let note = base.elements_mut().remove("note").unwrap();
+        let note = Wrap::<P>::try_new(note).unwrap_err();
+        let note = Wrap::<Span>::try_new(note).unwrap();
+        assert_eq!(note.id().to_string(), note_id);
+    }
+
+    #[test]
+    fn typed_handles() {
+        let html = format!("<div class=\"{}\" id=\"card\"></div>", COMPONENT_MARK);
+        let view = View::new_headless();
+        let base = Class::try_from_html(&html).unwrap().into_builder().build(view.clone());
+        let mut root = view.root_component();
+        let card = root.write().add_component(Box::new(base)).unwrap();
+
+        let name = card.read().name().to_owned();
+        let card = ComponentHandleT::<RootComponent>::try_new(card).unwrap_err();
+        let mut card = ComponentHandleT::<ComponentBase>::try_new(card).unwrap();
+        assert_eq!(card.read().name(), &name);
+        card.write().elements_mut().clear();
+        assert!(card.handle().read().elements().is_empty());
+    }
 }
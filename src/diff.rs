@@ -0,0 +1,384 @@
+use std::collections::HashMap;
+use htmldom_read::Node;
+use serde_json::Value;
+use crate::component::COMPONENT_MARK;
+
+/// Change of the page that brings the element to the desired state. Elements are found by
+/// the path of indices among element children starting from the component root element.
+/// Patches are expected to be applied in the order they were generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Patch {
+
+    /// Set attribute of the element.
+    SetAttribute {
+        path: Vec<usize>,
+        name: String,
+        value: String,
+    },
+
+    /// Remove attribute of the element.
+    RemoveAttribute {
+        path: Vec<usize>,
+        name: String,
+    },
+
+    /// Replace text of the element that has no element children.
+    SetText {
+        path: Vec<usize>,
+        text: String,
+    },
+
+    /// Insert element child at given index.
+    Insert {
+        path: Vec<usize>,
+        index: usize,
+        html: String,
+    },
+
+    /// Remove element child at given index.
+    Remove {
+        path: Vec<usize>,
+        index: usize,
+    },
+
+    /// Replace the whole element.
+    Replace {
+        path: Vec<usize>,
+        html: String,
+    },
+}
+
+impl Patch {
+
+    /// Path of the element this patch changes.
+    pub fn path(&self) -> &Vec<usize> {
+        use Patch::*;
+        match self {
+            SetAttribute { path, .. } => path,
+            RemoveAttribute { path, .. } => path,
+            SetText { path, .. } => path,
+            Insert { path, .. } => path,
+            Remove { path, .. } => path,
+            Replace { path, .. } => path,
+        }
+    }
+
+    /// JS code that applies this patch. Component root element is expected in variable `r`.
+    pub fn js(&self) -> String {
+        use Patch::*;
+        let e = element_js(self.path());
+        let s = |s: &str| Value::String(s.to_owned());
+        match self {
+            SetAttribute { name, value, .. } =>
+                format!("{}.setAttribute({}, {});", e, s(name), s(value)),
+            RemoveAttribute { name, .. } =>
+                format!("{}.removeAttribute({});", e, s(name)),
+            SetText { text, .. } =>
+                format!("{}.textContent = {};", e, s(text)),
+            Insert { index, html, .. } => format!("\
+                var p = {e};\
+                if ({index} < p.children.length) {{\
+                    p.children[{index}].insertAdjacentHTML('beforebegin', {html});\
+                }} else {{\
+                    p.insertAdjacentHTML('beforeend', {html});\
+                }}\
+            ", e = e, index = index, html = s(html)),
+            Remove { index, .. } =>
+                format!("{}.children[{}].remove();", e, index),
+            Replace { html, .. } =>
+                format!("{}.outerHTML = {};", e, s(html)),
+        }
+    }
+}
+
+/// JS expression that gives the element by path from the root `r`.
+fn element_js(path: &[usize]) -> String {
+    let mut s = String::from("r");
+    for i in path {
+        s.push_str(&format!(".children[{}]", i));
+    }
+    s
+}
+
+/// JS code that applies all patches to the component with given root ID.
+pub fn patches_js(root: &str, patches: &[Patch]) -> String {
+    let mut js = format!("var r = document.getElementById('{}');", root);
+    for patch in patches {
+        js.push_str(&patch.js());
+    }
+    js
+}
+
+/// First element of the root node or the node itself if it is not a root.
+pub fn element(node: &Node) -> Option<&Node> {
+    if node.is_root() {
+        node.children().iter().map(|c| &**c).find(|c| c.tag_name().is_some())
+    } else {
+        Some(node)
+    }
+}
+
+/// Find patches that turn old element into the new one. Nested components, which are
+/// descendants marked with `COMPONENT_MARK`, are left as they are on the page and
+/// the same nodes in the new element are ignored. Components whose container gets
+/// replaced are removed from the page with it.
+pub fn diff(old: &Node, new: &Node) -> Vec<Patch> {
+    let mut patches = Vec::new();
+    diff_node(old, new, &mut Vec::new(), &mut patches);
+    patches
+}
+
+fn diff_node(old: &Node, new: &Node, path: &mut Vec<usize>, patches: &mut Vec<Patch>) {
+    if is_replaced(old, new) {
+        patches.push(Patch::Replace {
+            path: path.clone(),
+            html: new.to_string(),
+        });
+        return;
+    }
+    if text(old) != text(new) {
+        patches.push(Patch::SetText {
+            path: path.clone(),
+            text: text(new),
+        });
+    }
+
+    let old_attrs = attributes(old);
+    let new_attrs = attributes(new);
+    let mut names: Vec<&String> = new_attrs.keys().collect();
+    names.sort();
+    for name in names {
+        let value = new_attrs.get(name).unwrap();
+        if old_attrs.get(name) != Some(value) {
+            patches.push(Patch::SetAttribute {
+                path: path.clone(),
+                name: name.to_owned(),
+                value: value.to_owned(),
+            });
+        }
+    }
+    let mut names: Vec<&String> = old_attrs.keys()
+        .filter(|n| !new_attrs.contains_key(*n))
+        .collect();
+    names.sort();
+    for name in names {
+        patches.push(Patch::RemoveAttribute {
+            path: path.clone(),
+            name: name.to_owned(),
+        });
+    }
+
+    // Old children are paired with their indices on the page, which also count nested
+    // components.
+    let old_children: Vec<(usize, &Node)> = element_children(old).into_iter()
+        .enumerate()
+        .filter(|(_, c)| !is_nested(c))
+        .collect();
+    let new_children = plain_children(new);
+    let nested = nested_places(old);
+
+    // Changes inside common children do not move their siblings, removals go from the end
+    // so indices of the remaining children stay valid.
+    let common = old_children.len().min(new_children.len());
+    for i in 0..common {
+        path.push(old_children[i].0);
+        diff_node(old_children[i].1, new_children[i], path, patches);
+        path.pop();
+    }
+    for i in (common..old_children.len()).rev() {
+        patches.push(Patch::Remove {
+            path: path.clone(),
+            index: old_children[i].0,
+        });
+    }
+
+    // Nested components stay right before the child that had the same index among other
+    // children, or at the end.
+    for i in common..new_children.len() {
+        let before = nested.iter().filter(|(k, _)| *k <= i).count();
+        patches.push(Patch::Insert {
+            path: path.clone(),
+            index: i + before,
+            html: new_children[i].to_string(),
+        });
+    }
+}
+
+/// Put nested components of the old element to the new one at the places where `diff`
+/// keeps them on the page. Nested components given in the new element are dropped.
+/// Children of the new element must be owned.
+pub fn keep_nested(old: &Node, new: &mut Node) {
+    if is_replaced(old, new) {
+        return;
+    }
+
+    {
+        let old_children = plain_children(old);
+        let new_children = new.children_mut().iter_mut()
+            .filter_map(|c| c.try_mut())
+            .filter(|c| c.tag_name().is_some() && !is_nested(c));
+        for (old, new) in old_children.into_iter().zip(new_children) {
+            keep_nested(old, new);
+        }
+    }
+
+    let nested = nested_places(old);
+    let given = element_children(new).into_iter().any(is_nested);
+    if nested.is_empty() && !given {
+        return;
+    }
+
+    let mut parts = Vec::new();
+    let mut nested = nested.into_iter().peekable();
+    let mut index = 0;
+    for child in new.children().iter() {
+        if child.tag_name().is_some() {
+            if is_nested(child) {
+                continue;
+            }
+            while nested.peek().map(|(k, _)| *k <= index).unwrap_or(false) {
+                parts.push(nested.next().unwrap().1.to_string());
+            }
+            index += 1;
+        }
+        parts.push(child.to_string());
+    }
+    for (_, node) in nested {
+        parts.push(node.to_string());
+    }
+
+    let content = Node::from_html(&parts.concat(), &Default::default()).ok().and_then(|n| n);
+    if let Some(content) = content {
+        *new.children_mut() = content.children().to_all_owned();
+    }
+}
+
+/// Whether the old element must be replaced with the new one as a whole.
+fn is_replaced(old: &Node, new: &Node) -> bool {
+    // Elements with other tag or ID are different elements.
+    if old.tag_name() != new.tag_name() || id(old) != id(new) {
+        return true;
+    }
+
+    // Text mixed with elements cannot be changed without touching the elements.
+    text(old) != text(new)
+        && !(element_children(old).is_empty() && plain_children(new).is_empty())
+}
+
+/// Whether the element is the root of the nested component.
+fn is_nested(node: &Node) -> bool {
+    node.attribute_by_name("class")
+        .map(|a| a.values().contains(&COMPONENT_MARK.to_string()))
+        .unwrap_or(false)
+}
+
+/// Element children which are not nested components.
+fn plain_children(node: &Node) -> Vec<&Node> {
+    element_children(node).into_iter().filter(|c| !is_nested(c)).collect()
+}
+
+/// Nested components among the element children with the number of other element
+/// children that precede each of them.
+fn nested_places(node: &Node) -> Vec<(usize, &Node)> {
+    let mut vec = Vec::new();
+    let mut index = 0;
+    for child in element_children(node) {
+        if is_nested(child) {
+            vec.push((index, child));
+        } else {
+            index += 1;
+        }
+    }
+    vec
+}
+
+fn id(node: &Node) -> Option<&String> {
+    node.attribute_by_name("id").map(|a| a.first_value())
+}
+
+fn element_children(node: &Node) -> Vec<&Node> {
+    node.children().iter()
+        .map(|c| &**c)
+        .filter(|c| c.tag_name().is_some())
+        .collect()
+}
+
+/// Concatenated text of the direct text children.
+fn text(node: &Node) -> String {
+    node.children().iter()
+        .filter(|c| c.tag_name().is_none())
+        .filter_map(|c| c.text())
+        .collect()
+}
+
+fn attributes(node: &Node) -> HashMap<String, String> {
+    node.attributes().iter()
+        .map(|a| (a.name().to_owned(), a.values_to_string()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::component::COMPONENT_MARK;
+    use crate::diff::{diff, element, keep_nested, Patch};
+    use htmldom_read::Node;
+
+    fn node(html: &str) -> Node {
+        Node::from_html(html, &Default::default()).unwrap().unwrap()
+    }
+
+    #[test]
+    fn diff_attributes_text_children() {
+        let old = node("<div id='a' class='x'><p>one</p><p>two</p><p>three</p></div>");
+        let new = node("<div id='a' title='t'><p>one</p><p>2</p></div>");
+        let patches = diff(element(&old).unwrap(), element(&new).unwrap());
+        assert_eq!(patches, vec![
+            Patch::SetAttribute {
+                path: vec![],
+                name: "title".to_string(),
+                value: "t".to_string(),
+            },
+            Patch::RemoveAttribute {
+                path: vec![],
+                name: "class".to_string(),
+            },
+            Patch::SetText {
+                path: vec![1],
+                text: "2".to_string(),
+            },
+            Patch::Remove {
+                path: vec![],
+                index: 2,
+            },
+        ]);
+    }
+
+    #[test]
+    fn nested_components_kept() {
+        let old = node(&format!("<div id='a'><ul>\
+            <li>1</li><div class='{}' id='c1'></div><li>2</li>\
+            </ul></div>", COMPONENT_MARK));
+        let mut new = node("<div id='a'><ul><li>one</li><li>2</li><li>3</li></ul></div>");
+        let patches = diff(element(&old).unwrap(), element(&new).unwrap());
+        assert_eq!(patches.len(), 2);
+        assert_eq!(patches[0], Patch::SetText {
+            path: vec![0, 0],
+            text: "one".to_string(),
+        });
+        match &patches[1] {
+            Patch::Insert { path, index, .. } => assert_eq!((path, *index), (&vec![0], 3)),
+            _ => panic!("unexpected patch"),
+        }
+
+        let owned = new.children().to_all_owned();
+        *new.children_mut() = owned;
+        let element_mut = new.children_mut().iter_mut()
+            .filter_map(|c| c.try_mut())
+            .find(|c| c.tag_name().is_some())
+            .unwrap();
+        keep_nested(element(&old).unwrap(), element_mut);
+        let html = new.to_string();
+        let pos = |s: &str| html.find(s).unwrap();
+        assert!(pos(">one<") < pos("c1") && pos("c1") < pos(">2<"));
+    }
+}
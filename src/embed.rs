@@ -0,0 +1,288 @@
+use std::path::{Path, PathBuf};
+use std::fmt::Write;
+use crate::component::{Class, ClassLoadError};
+
+/// Name of the file generated in `OUT_DIR` if no other name was given.
+pub const DEFAULT_OUT_FILE: &'static str = "uitaco_classes.rs";
+
+/// Include Rust code generated by `Embed` from the build script.
+/// Accepts the name of the file in `OUT_DIR`.
+#[macro_export]
+macro_rules! include_classes {
+    () => {
+        include!(concat!(env!("OUT_DIR"), "/uitaco_classes.rs"));
+    };
+    ($name: literal) => {
+        include!(concat!(env!("OUT_DIR"), "/", $name));
+    };
+}
+
+/// Builder of Rust code that embeds component HTML files into the binary. It is expected to
+/// be used from the build script. Each file is loaded with the same rules as
+/// `Class::all_from_html` and any invalid class fails the build.
+///
+/// For each file a module is generated with `HTML` constant that holds the file content.
+/// It contains a module for each class with `CLASS` constant for the class name and
+/// a constant for each element ID of the class. File `forms.html` with class `textInput`
+/// which has element `inputLabel` will give `forms::text_input::INPUT_LABEL`.
+#[derive(Clone, Debug, Default)]
+pub struct Embed {
+    files: Vec<PathBuf>,
+
+    /// Directories that were scanned for files.
+    dirs: Vec<PathBuf>,
+}
+
+/// Error that occur while generating code for embedded classes.
+#[derive(Debug)]
+pub enum EmbedError {
+
+    /// File cannot be read.
+    Io(PathBuf, std::io::Error),
+
+    /// Classes of the file cannot be loaded.
+    Class(PathBuf, ClassLoadError),
+
+    /// Two names give the same Rust identifier in one module.
+    NameClash {
+        file: PathBuf,
+        ident: String,
+    },
+}
+
+impl Embed {
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Embed given HTML file.
+    pub fn file<P: AsRef<Path>>(mut self, path: P) -> Self {
+        self.files.push(path.as_ref().to_owned());
+        self
+    }
+
+    /// Embed all HTML files found in the directory and it's subdirectories. Modules are
+    /// named after the files only, so files with the same name in different directories
+    /// clash.
+    pub fn dir<P: AsRef<Path>>(mut self, path: P) -> Result<Self, EmbedError> {
+        let path = path.as_ref();
+        let mut files = Vec::new();
+        collect_dir(path, &mut files)?;
+        files.sort();
+        self.files.append(&mut files);
+        self.dirs.push(path.to_owned());
+        Ok(self)
+    }
+
+    /// Validate all files and generate Rust code for them.
+    pub fn generate(&self) -> Result<String, EmbedError> {
+        let mut code = String::new();
+        let mut modules = Vec::with_capacity(self.files.len());
+        for path in &self.files {
+            let html = std::fs::read_to_string(path)
+                .map_err(|e| EmbedError::Io(path.to_owned(), e))?;
+            let classes = Class::all_from_html_checked(&html)
+                .map_err(|e| EmbedError::Class(path.to_owned(), e))?;
+
+            let module = snake_case(&path.file_stem().unwrap().to_string_lossy());
+            check_clash(&mut modules, module.clone(), path)?;
+            let abs = std::fs::canonicalize(path)
+                .map_err(|e| EmbedError::Io(path.to_owned(), e))?;
+
+            writeln!(code, "/// Classes embedded from `{}`.", path.display()).unwrap();
+            writeln!(code, "pub mod {} {{", module).unwrap();
+            writeln!(code, "    pub const HTML: &'static str = include_str!({:?});",
+                abs.to_string_lossy()).unwrap();
+
+            let mut names: Vec<&String> = classes.keys().collect();
+            names.sort();
+            let mut class_modules = Vec::with_capacity(names.len());
+            for name in names {
+                let class = classes.get(name).unwrap();
+                let class_module = snake_case(name);
+                check_clash(&mut class_modules, class_module.clone(), path)?;
+
+                writeln!(code).unwrap();
+                writeln!(code, "    pub mod {} {{", class_module).unwrap();
+                writeln!(code, "        pub const CLASS: &'static str = {:?};", name).unwrap();
+
+                let mut ids: Vec<&String> = class.placeholders().keys()
+                    .filter(|id| *id != name)
+                    .collect();
+                ids.sort();
+                let mut consts = vec!["CLASS".to_string()];
+                for id in ids {
+                    let ident = upper_snake_case(id);
+                    check_clash(&mut consts, ident.clone(), path)?;
+                    writeln!(code, "        pub const {}: &'static str = {:?};", ident, id)
+                        .unwrap();
+                }
+                writeln!(code, "    }}").unwrap();
+            }
+            writeln!(code, "}}").unwrap();
+            writeln!(code).unwrap();
+
+            modules.push(module);
+        }
+        Ok(code)
+    }
+
+    /// Generate code and write it to the file in `OUT_DIR`. Cargo is asked to rerun the build
+    /// script when any of the embedded files or scanned directories change, so new files
+    /// get embedded too. Get path of the written file.
+    ///
+    /// # Panics
+    /// Function panics if it is called outside of the build script.
+    pub fn write_to_out_dir(&self, name: &str) -> Result<PathBuf, EmbedError> {
+        for path in self.dirs.iter().chain(&self.files) {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+
+        let code = self.generate()?;
+        let out = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join(name);
+        std::fs::write(&out, code).map_err(|e| EmbedError::Io(out.clone(), e))?;
+        Ok(out)
+    }
+}
+
+/// Find HTML files in the directory and it's subdirectories.
+fn collect_dir(path: &Path, files: &mut Vec<PathBuf>) -> Result<(), EmbedError> {
+    let dir = std::fs::read_dir(path)
+        .map_err(|e| EmbedError::Io(path.to_owned(), e))?;
+    for entry in dir {
+        let entry = entry.map_err(|e| EmbedError::Io(path.to_owned(), e))?;
+        let path = entry.path();
+        if path.is_dir() {
+            collect_dir(&path, files)?;
+        } else if path.is_file() && path.extension().map(|e| e == "html").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Add identifier to the list failing if it is already there.
+fn check_clash(list: &mut Vec<String>, ident: String, file: &Path) -> Result<(), EmbedError> {
+    if list.contains(&ident) {
+        Err(EmbedError::NameClash {
+            file: file.to_owned(),
+            ident,
+        })
+    } else {
+        list.push(ident);
+        Ok(())
+    }
+}
+
+/// Split name into lowercase words. Words are separated by case change, digits boundaries are
+/// kept and all characters that cannot be used in identifiers are separators.
+fn words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(word);
+                word = String::new();
+            }
+            prev_lower = false;
+            continue;
+        }
+
+        if c.is_uppercase() && prev_lower && !word.is_empty() {
+            words.push(word);
+            word = String::new();
+        }
+        prev_lower = c.is_lowercase() || c.is_numeric();
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/// Rust keywords that cannot be used as identifiers.
+const KEYWORDS: &'static [&'static str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+    "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+    "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true",
+    "try", "type", "unsafe", "use", "where", "while", "abstract", "become", "box", "do",
+    "final", "macro", "override", "priv", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Make valid identifier from the words joined by underscore.
+fn ident(words: Vec<String>) -> String {
+    let s = words.join("_");
+    if s.is_empty() || s.chars().next().unwrap().is_numeric() {
+        format!("_{}", s)
+    } else if KEYWORDS.contains(&s.as_str()) {
+        format!("{}_", s)
+    } else {
+        s
+    }
+}
+
+/// Convert name to `snake_case` identifier for modules.
+pub fn snake_case(name: &str) -> String {
+    ident(words(name))
+}
+
+/// Convert name to `UPPER_SNAKE_CASE` identifier for constants.
+pub fn upper_snake_case(name: &str) -> String {
+    snake_case(name).to_uppercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::component::{ClassLoadError, COMPONENT_MARK};
+    use crate::embed::{snake_case, upper_snake_case, Embed, EmbedError};
+
+    #[test]
+    fn identifiers() {
+        assert_eq!(snake_case("userCard"), "user_card");
+        assert_eq!(snake_case("todo-list"), "todo_list");
+        assert_eq!(snake_case("HTMLView"), "htmlview");
+        assert_eq!(snake_case("2col"), "_2col");
+        assert_eq!(snake_case("type"), "type_");
+        assert_eq!(upper_snake_case("inputLabel"), "INPUT_LABEL");
+        assert_eq!(upper_snake_case("item3Title"), "ITEM3_TITLE");
+    }
+
+    #[test]
+    fn generate() {
+        let dir = std::env::temp_dir().join(format!("uitaco-embed-{}", std::process::id()));
+        fs::create_dir_all(dir.join("forms")).unwrap();
+        fs::write(dir.join("forms").join("inputs.html"), format!("\
+            <div class=\"{}\" id=\"textInput\">\
+                <label id=\"inputLabel\"></label>\
+            </div>\
+        ", COMPONENT_MARK)).unwrap();
+
+        let code = Embed::new().dir(&dir).unwrap().generate().unwrap();
+        assert!(code.contains("pub mod inputs {"));
+        assert!(code.contains("pub const HTML: &'static str = include_str!("));
+        assert!(code.contains("pub mod text_input {"));
+        assert!(code.contains("pub const CLASS: &'static str = \"textInput\";"));
+        assert!(code.contains("pub const INPUT_LABEL: &'static str = \"inputLabel\";"));
+
+        // The same file name in another directory gives the same module.
+        fs::write(dir.join("inputs.html"), "<div></div>").unwrap();
+        match Embed::new().dir(&dir).unwrap().generate() {
+            Err(EmbedError::NameClash { ident, .. }) => assert_eq!(ident, "inputs"),
+            _ => panic!("name clash was not found"),
+        }
+        fs::remove_file(dir.join("inputs.html")).unwrap();
+
+        fs::write(dir.join("broken.html"), format!("<p class=\"{}\"></p>", COMPONENT_MARK))
+            .unwrap();
+        match Embed::new().dir(&dir).unwrap().generate() {
+            Err(EmbedError::Class(_, ClassLoadError::InvalidClass(_))) => (),
+            _ => panic!("invalid class was embedded"),
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,233 @@
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::ViewWrap;
+use crate::component::{ComponentHandle, ComponentId};
+
+/// Descriptor of the registered event handler.
+pub type HandlerId = usize;
+
+/// Function that handles events of type `E` emitted by the component or any of it's
+/// sub-components. Events can be emitted from any thread so handlers must be shareable
+/// between threads.
+pub type HandlerFn<E> = dyn Fn(&ViewWrap, &mut ComponentEvent<E>) + Send + Sync;
+
+/// Custom event emitted by the component. It is first given to the handlers of the
+/// component itself and then bubbles up through the containers of the component to the
+/// root component until some handler stops propagation.
+#[derive(Debug)]
+pub struct ComponentEvent<E> {
+    data: E,
+
+    /// Component that emitted the event.
+    source: ComponentHandle,
+
+    /// Component which handlers are running.
+    current: ComponentHandle,
+
+    stopped: bool,
+    handled: bool,
+}
+
+/// Event handlers of all components of the view.
+#[derive(Default)]
+pub struct Handlers {
+    next_id: HandlerId,
+
+    /// Handlers of each component and event type in the order they were added. Each handler
+    /// is `Arc<HandlerFn<E>>` for the type of the event.
+    map: HashMap<(ComponentId, TypeId), Vec<(HandlerId, Box<dyn Any + Send + Sync>)>>,
+}
+
+impl<E> ComponentEvent<E> {
+
+    pub fn new(source: ComponentHandle, data: E) -> Self {
+        ComponentEvent {
+            data,
+            current: source.clone(),
+            source,
+            stopped: false,
+            handled: false,
+        }
+    }
+
+    pub fn data(&self) -> &E {
+        &self.data
+    }
+
+    /// Data can be changed by handlers to pass results to the emitter or to the handlers
+    /// of parent components.
+    pub fn data_mut(&mut self) -> &mut E {
+        &mut self.data
+    }
+
+    pub fn into_data(self) -> E {
+        self.data
+    }
+
+    /// Component that emitted the event.
+    pub fn source(&self) -> &ComponentHandle {
+        &self.source
+    }
+
+    /// Component which handlers are running. It is not locked while handlers run.
+    pub fn current(&self) -> &ComponentHandle {
+        &self.current
+    }
+
+    /// Do not pass the event to parent components. Other handlers of the current component
+    /// still get the event.
+    pub fn stop_propagation(&mut self) {
+        self.stopped = true;
+    }
+
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Mark the event as handled so the emitter knows some component reacted to it.
+    pub fn set_handled(&mut self) {
+        self.handled = true;
+    }
+
+    pub fn is_handled(&self) -> bool {
+        self.handled
+    }
+}
+
+impl Handlers {
+
+    /// Add handler of events of type `E` to the component with given ID.
+    pub fn add<E: 'static>(&mut self, component: ComponentId, handler: Arc<HandlerFn<E>>)
+            -> HandlerId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.map.entry((component, TypeId::of::<E>()))
+            .or_insert_with(Vec::new)
+            .push((id, Box::new(handler)));
+        id
+    }
+
+    /// Remove the handler. Get whether it was found.
+    pub fn remove(&mut self, id: HandlerId) -> bool {
+        let mut found = false;
+        for list in self.map.values_mut() {
+            let len = list.len();
+            list.retain(|(i, _)| *i != id);
+            found |= list.len() != len;
+        }
+        self.map.retain(|_, list| !list.is_empty());
+        found
+    }
+
+    /// Remove all handlers of the component.
+    pub fn remove_component(&mut self, component: ComponentId) {
+        self.map.retain(|(c, _), _| *c != component);
+    }
+
+    /// Handlers of events of type `E` of the component in the order they were added.
+    pub fn get<E: 'static>(&self, component: ComponentId) -> Vec<Arc<HandlerFn<E>>> {
+        self.map.get(&(component, TypeId::of::<E>()))
+            .map(|list| list.iter()
+                .filter_map(|(_, h)| h.downcast_ref::<Arc<HandlerFn<E>>>())
+                .cloned()
+                .collect())
+            .unwrap_or_default()
+    }
+}
+
+/// Components from the given one up to the root of the tree. If component is not in the tree
+/// then only the component itself is returned. The tree is searched from the root and every
+/// visited component is read-locked for a moment: ancestors of the component, components
+/// that come before it and their sub-components. The component itself is not locked.
+pub fn bubble_path(root: &ComponentHandle, component: &ComponentHandle)
+        -> Vec<ComponentHandle> {
+    let mut path = ancestors(root, component).unwrap_or_default();
+    path.insert(0, component.clone());
+    path
+}
+
+/// Ancestors of the component starting from it's parent.
+fn ancestors(from: &ComponentHandle, component: &ComponentHandle)
+        -> Option<Vec<ComponentHandle>> {
+    if from == component {
+        return Some(Vec::new());
+    }
+
+    let children: Vec<ComponentHandle> = {
+        let guard = from.read();
+        guard.components().iter().cloned().collect()
+    };
+    for child in &children {
+        if let Some(mut path) = ancestors(child, component) {
+            path.push(from.clone());
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Run handlers of the components on the path in order until propagation is stopped.
+pub fn dispatch<E: 'static>(view: &ViewWrap, path: Vec<ComponentHandle>,
+                            event: &mut ComponentEvent<E>) {
+    for component in path {
+        let handlers = {
+            let guard = view.inner.view.read().unwrap();
+            guard.event_handlers.get::<E>(component.id())
+        };
+        event.current = component;
+        for handler in handlers {
+            handler(view, event);
+        }
+        if event.stopped {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use crate::View;
+    use crate::component::{Class, COMPONENT_MARK};
+    use crate::emit::ComponentEvent;
+
+    #[test]
+    fn events_bubble() {
+        let view = View::new_headless();
+        let html = format!("<div class=\"{}\" id=\"card\"></div>", COMPONENT_MARK);
+        let base = Class::try_from_html(&html).unwrap().into_builder().build(view.clone());
+        let mut root = view.root_component();
+        let card = root.write().add_component(Box::new(base)).unwrap();
+
+        let log = Arc::new(Mutex::new(Vec::new()));
+        let l = log.clone();
+        card.on_event(move |_, e: &mut ComponentEvent<u32>| {
+            l.lock().unwrap().push(("card", *e.data()));
+            *e.data_mut() += 1;
+        });
+        let l = log.clone();
+        root.on_event(move |_, e: &mut ComponentEvent<u32>| {
+            l.lock().unwrap().push(("root", *e.data()));
+            e.set_handled();
+            if *e.data() > 10 {
+                e.stop_propagation();
+            }
+        });
+        let l = log.clone();
+        let stopper = card.on_event(move |_, e: &mut ComponentEvent<u32>| {
+            l.lock().unwrap().push(("stop", *e.data()));
+            e.stop_propagation();
+        });
+
+        let event = card.emit(1u32);
+        assert!(!event.is_handled());
+        assert_eq!(event.into_data(), 2);
+
+        assert!(view.remove_event_handler(stopper));
+        assert!(card.emit(5u32).is_handled());
+        assert!(!card.emit("other type").is_handled());
+        assert_eq!(*log.lock().unwrap(),
+            vec![("card", 1), ("stop", 2), ("card", 5), ("root", 6)]);
+    }
+}
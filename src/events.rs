@@ -83,7 +83,8 @@ impl<E> Event for OnClick<E>
             self.remove_callback();
         }
 
-        let id = self.elem.view_mut().add_callback(callback);
+        let owner = self.elem.id().to_owned();
+        let id = self.elem.view_mut().add_callback(callback, &owner);
         self.elem.set_attribute("onclick", &default_callback_fn(id));
 
         self.callback_id = Some(id);
@@ -95,7 +96,8 @@ impl<E> Event for OnClick<E>
         }
 
         self.elem.set_attribute("onclick", "");
-        Some(self.elem.view_mut().remove_callback(self.callback_id.unwrap()))
+        let id = self.callback_id.take().unwrap();
+        Some(self.elem.view_mut().remove_callback(id))
     }
 
     fn is_set(&self) -> bool {
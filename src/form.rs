@@ -0,0 +1,209 @@
+use serde_derive::Deserialize;
+use serde_json::Value;
+
+/// JS functions that read and write value of the form control with respect to it's type.
+/// Checkboxes give booleans, number inputs give numbers, multiple selects give arrays and
+/// radio buttons give the value of the checked one.
+const VALUE_FNS: &'static str = "\
+    var isControl = function(e) {\
+        return e != null && ['INPUT', 'SELECT', 'TEXTAREA'].indexOf(e.tagName) >= 0;\
+    };\
+    var readValue = function(e) {\
+        if (e.type == 'checkbox') {\
+            return e.checked;\
+        } else if (e.type == 'number' || e.type == 'range') {\
+            return isNaN(e.valueAsNumber) ? null : e.valueAsNumber;\
+        } else if (e.tagName == 'SELECT' && e.multiple) {\
+            var arr = [];\
+            for (var i = 0; i < e.options.length; i++) {\
+                if (e.options[i].selected) {\
+                    arr.push(e.options[i].value);\
+                }\
+            }\
+            return arr;\
+        }\
+        return e.value;\
+    };\
+    var writeValue = function(e, v) {\
+        if (e.type == 'checkbox') {\
+            e.checked = !!v;\
+        } else if (e.type == 'radio') {\
+            e.checked = e.value == v;\
+        } else if (e.tagName == 'SELECT' && e.multiple) {\
+            for (var i = 0; i < e.options.length; i++) {\
+                e.options[i].selected = Array.isArray(v) && v.indexOf(e.options[i].value) >= 0;\
+            }\
+        } else {\
+            e.value = v == null ? '' : v;\
+        }\
+    };\
+    var fieldOf = function(e) {\
+        for (var k in ids) {\
+            if (ids[k] == e.id) {\
+                return k;\
+            }\
+        }\
+        return e.name ? e.name : null;\
+    };\
+";
+
+/// Error that occur while reading or writing the form.
+#[derive(Debug)]
+pub enum FormError {
+
+    /// Page did not respond, likely because the component was removed.
+    NoResponse,
+
+    /// Form values do not match the struct.
+    Deserialize(serde_json::Error),
+
+    /// Struct cannot be serialized to JSON.
+    Serialize(serde_json::Error),
+
+    /// Struct must be serialized to an object.
+    NotAnObject,
+}
+
+/// Change of the form control made by the user. These are passed as JSON in arguments
+/// of the form change callback.
+#[derive(Clone, Debug, Deserialize)]
+pub struct FormChange {
+
+    /// Original placeholder ID or the name of the changed control.
+    pub field: String,
+
+    /// New value of the control.
+    pub value: Value,
+}
+
+impl FormChange {
+
+    /// Parse arguments of the form change callback.
+    pub fn from_args(args: &str) -> Option<Self> {
+        serde_json::from_str(args).ok()
+    }
+}
+
+/// JS code that reads all controls of the component into object and sends it as a response
+/// to the request. Controls are found by their IDs given in the map of field names
+/// to generated IDs and by `name` attribute.
+pub fn read_js(root: &str, ids: &Value, request: usize) -> String {
+    format!("\
+        var ids = {ids};\
+        {fns}\
+        var out = {{}};\
+        for (var k in ids) {{\
+            var e = document.getElementById(ids[k]);\
+            if (isControl(e) && e.type != 'radio') {{\
+                out[k] = readValue(e);\
+            }}\
+        }}\
+        var named = document.getElementById('{root}').querySelectorAll('[name]');\
+        for (var i = 0; i < named.length; i++) {{\
+            var e = named[i];\
+            if (!isControl(e) || (e.type == 'radio' && !e.checked)) {{\
+                continue;\
+            }}\
+            if (!out.hasOwnProperty(e.name)) {{\
+                out[e.name] = readValue(e);\
+            }}\
+        }}\
+        window.external.invoke(JSON.stringify({{\
+            incmd: 'attribute',\
+            request: {request},\
+            value: JSON.stringify(out)\
+        }}));\
+    ", ids = ids, fns = VALUE_FNS, root = root, request = request)
+}
+
+/// JS code that writes values of the object into controls of the component.
+pub fn write_js(root: &str, ids: &Value, values: &Value) -> String {
+    format!("\
+        var ids = {ids};\
+        var values = {values};\
+        {fns}\
+        for (var k in values) {{\
+            var e = ids.hasOwnProperty(k) ? document.getElementById(ids[k]) : null;\
+            if (isControl(e)) {{\
+                writeValue(e, values[k]);\
+                continue;\
+            }}\
+            var named = document.getElementById('{root}')\
+                .querySelectorAll('[name=\"' + k + '\"]');\
+            for (var i = 0; i < named.length; i++) {{\
+                if (isControl(named[i])) {{\
+                    writeValue(named[i], values[k]);\
+                }}\
+            }}\
+        }}\
+    ", ids = ids, values = values, fns = VALUE_FNS, root = root)
+}
+
+/// JS code that calls the callback with `FormChange` arguments when user changes any
+/// control of the component. Text controls report on `input` and checkboxes, radio
+/// buttons and selects on `change`, so each user action is reported once.
+pub fn listen_js(root: &str, ids: &Value, callback: usize) -> String {
+    format!("\
+        var ids = {ids};\
+        {fns}\
+        var root = document.getElementById('{root}');\
+        if (root.uitacoFormListener) {{\
+            root.removeEventListener('input', root.uitacoFormListener);\
+            root.removeEventListener('change', root.uitacoFormListener);\
+        }}\
+        root.uitacoFormListener = function(ev) {{\
+            var e = ev.target;\
+            if (!isControl(e) || (e.type == 'radio' && !e.checked)) {{\
+                return;\
+            }}\
+            var choice = e.type == 'checkbox' || e.type == 'radio' || e.tagName == 'SELECT';\
+            if ((ev.type == 'change') != choice) {{\
+                return;\
+            }}\
+            var field = fieldOf(e);\
+            if (field == null) {{\
+                return;\
+            }}\
+            window.external.invoke(JSON.stringify({{\
+                incmd: 'callback',\
+                descriptor: {callback},\
+                args: JSON.stringify({{ field: field, value: readValue(e) }})\
+            }}));\
+        }};\
+        root.addEventListener('input', root.uitacoFormListener);\
+        root.addEventListener('change', root.uitacoFormListener);\
+    ", ids = ids, fns = VALUE_FNS, root = root, callback = callback)
+}
+
+/// JS code that stops listening for changes of the form.
+pub fn unlisten_js(root: &str) -> String {
+    format!("\
+        var root = document.getElementById('{root}');\
+        if (root != null && root.uitacoFormListener) {{\
+            root.removeEventListener('input', root.uitacoFormListener);\
+            root.removeEventListener('change', root.uitacoFormListener);\
+            root.uitacoFormListener = undefined;\
+        }}\
+    ", root = root)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::form::{listen_js, FormChange};
+    use serde_json::Value;
+
+    #[test]
+    fn form_change_args() {
+        let change = FormChange::from_args(r#"{"field":"email","value":"a@b.c"}"#).unwrap();
+        assert_eq!(change.field, "email");
+        assert_eq!(change.value, Value::String("a@b.c".to_string()));
+        assert!(FormChange::from_args("{}").is_none());
+    }
+
+    #[test]
+    fn listen_once_per_action() {
+        let js = listen_js("form", &Value::Object(Default::default()), 3);
+        assert!(js.contains("if ((ev.type == 'change') != choice) {"));
+        assert!(js.contains("descriptor: 3,"));
+    }
+}
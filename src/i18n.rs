@@ -0,0 +1,323 @@
+use std::collections::HashMap;
+use serde_json::Value;
+
+/// Attribute of the element in class HTML which holds the key of the translated text.
+pub const I18N_KEY_ATTR: &'static str = "data-i18n";
+
+/// Attribute of the element in class HTML which holds JSON object with arguments
+/// for translated text.
+pub const I18N_ARGS_ATTR: &'static str = "data-i18n-args";
+
+/// Name of the argument that is used to select plural form of the message.
+pub const COUNT_ARG: &'static str = "count";
+
+/// Arguments that get substituted into translated message. Message refers to the argument
+/// by it's name in curly braces, like `{name}`.
+pub type Args = HashMap<String, String>;
+
+/// Function that selects plural category for given number.
+pub type PluralRule = fn(u64) -> PluralCategory;
+
+/// Plural categories as they are defined by CLDR.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PluralCategory {
+    Zero,
+    One,
+    Two,
+    Few,
+    Many,
+    Other,
+}
+
+/// Translated message of the catalog.
+#[derive(Debug, Clone)]
+pub enum Message {
+
+    /// Message that does not depend on any count.
+    Text(String),
+
+    /// Message with forms for different plural categories. `Other` form is used
+    /// when there is no form for selected category.
+    Plural(HashMap<PluralCategory, String>),
+}
+
+/// Translation catalog of one locale.
+#[derive(Debug, Clone)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, Message>,
+    plural_rule: PluralRule,
+}
+
+/// Error that occur while loading catalog from JSON.
+#[derive(Debug)]
+pub enum CatalogLoadError {
+
+    /// Given string is not a valid JSON.
+    Json(serde_json::Error),
+
+    /// Catalog must be a JSON object which maps keys to the messages.
+    NotAnObject,
+
+    /// Message with given key is neither string nor object of plural forms.
+    InvalidMessage(String),
+
+    /// Plural form of the message with given key has unknown category name.
+    UnknownPluralCategory {
+        key: String,
+        category: String,
+    },
+}
+
+/// All catalogs registered for the view and currently selected locale.
+#[derive(Debug, Default)]
+pub struct Translations {
+    catalogs: HashMap<String, Catalog>,
+    locale: Option<String>,
+}
+
+/// Plural rule that fits English and many other languages.
+pub fn default_plural_rule(n: u64) -> PluralCategory {
+    if n == 1 {
+        PluralCategory::One
+    } else {
+        PluralCategory::Other
+    }
+}
+
+/// Substitute arguments into the message. Arguments that were not supplied are left as is.
+pub fn format(template: &str, args: &Args) -> String {
+    let mut s = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        s.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}');
+        if let Some(end) = end {
+            let name = &after[..end];
+            if let Some(val) = args.get(name) {
+                s.push_str(val);
+            } else {
+                s.push_str(&rest[start..start + end + 2]);
+            }
+            rest = &after[end + 1..];
+        } else {
+            s.push_str(&rest[start..]);
+            rest = "";
+        }
+    }
+    s.push_str(rest);
+    s
+}
+
+/// Read arguments from JSON object as it is stored in `data-i18n-args` attribute.
+/// Invalid JSON gives empty arguments.
+pub fn args_from_json(json: &str) -> Args {
+    let value: Value = if let Ok(v) = serde_json::from_str(json) {
+        v
+    } else {
+        return Default::default();
+    };
+
+    let mut args = Args::new();
+    if let Value::Object(map) = value {
+        for (k, v) in map {
+            let v = if let Value::String(s) = v {
+                s
+            } else {
+                v.to_string()
+            };
+            args.insert(k, v);
+        }
+    }
+    args
+}
+
+/// Write arguments to JSON object so they can be stored in `data-i18n-args` attribute.
+pub fn args_to_json(args: &Args) -> String {
+    serde_json::to_string(args).unwrap()
+}
+
+impl PluralCategory {
+
+    /// Get category by it's CLDR name like `one` or `other`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        use PluralCategory::*;
+        match name {
+            "zero"  => Some(Zero),
+            "one"   => Some(One),
+            "two"   => Some(Two),
+            "few"   => Some(Few),
+            "many"  => Some(Many),
+            "other" => Some(Other),
+            _       => None,
+        }
+    }
+}
+
+impl Catalog {
+
+    /// Create empty catalog for given locale.
+    pub fn new(locale: String) -> Self {
+        Catalog {
+            locale,
+            messages: Default::default(),
+            plural_rule: default_plural_rule,
+        }
+    }
+
+    /// Load catalog from JSON. Each key of the JSON object is a message key and the value is
+    /// either a string or an object which maps plural categories to the message forms:
+    /// `{ "items": { "one": "{count} item", "other": "{count} items" } }`.
+    pub fn from_json(locale: String, json: &str) -> Result<Self, CatalogLoadError> {
+        let value: Value = serde_json::from_str(json)
+            .map_err(|e| CatalogLoadError::Json(e))?;
+        let map = if let Value::Object(map) = value {
+            map
+        } else {
+            return Err(CatalogLoadError::NotAnObject);
+        };
+
+        let mut catalog = Catalog::new(locale);
+        for (key, val) in map {
+            match val {
+                Value::String(s) => catalog.insert_text(key, s),
+                Value::Object(forms) => {
+                    let mut plural = HashMap::with_capacity(forms.len());
+                    for (category, form) in forms {
+                        let c = PluralCategory::from_name(&category);
+                        if c.is_none() {
+                            return Err(CatalogLoadError::UnknownPluralCategory {
+                                key,
+                                category,
+                            });
+                        }
+                        if let Value::String(form) = form {
+                            plural.insert(c.unwrap(), form);
+                        } else {
+                            return Err(CatalogLoadError::InvalidMessage(key));
+                        }
+                    }
+                    catalog.insert_plural(key, plural);
+                },
+                _ => return Err(CatalogLoadError::InvalidMessage(key)),
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Use given plural rule for this catalog instead of the default one.
+    pub fn with_plural_rule(mut self, rule: PluralRule) -> Self {
+        self.plural_rule = rule;
+        self
+    }
+
+    /// Locale of this catalog.
+    pub fn locale(&self) -> &String {
+        &self.locale
+    }
+
+    /// Add message that has no plural forms.
+    pub fn insert_text(&mut self, key: String, text: String) {
+        self.messages.insert(key, Message::Text(text));
+    }
+
+    /// Add message with plural forms.
+    pub fn insert_plural(&mut self, key: String, forms: HashMap<PluralCategory, String>) {
+        self.messages.insert(key, Message::Plural(forms));
+    }
+
+    /// Get raw message by key.
+    pub fn message(&self, key: &str) -> Option<&Message> {
+        self.messages.get(key)
+    }
+
+    /// Translate message with given key. For plural messages `count` argument selects the form.
+    /// If it is absent or is not a number then `Other` form is used.
+    pub fn translate(&self, key: &str, args: &Args) -> Option<String> {
+        let template = match self.messages.get(key)? {
+            Message::Text(s) => s,
+            Message::Plural(forms) => {
+                let category = args.get(COUNT_ARG)
+                    .and_then(|s| s.parse::<u64>().ok())
+                    .map(|n| (self.plural_rule)(n))
+                    .unwrap_or(PluralCategory::Other);
+                if let Some(s) = forms.get(&category) {
+                    s
+                } else {
+                    forms.get(&PluralCategory::Other)?
+                }
+            },
+        };
+        Some(format(template, args))
+    }
+}
+
+impl Translations {
+
+    /// Register catalog. Catalog for the same locale gets replaced.
+    pub fn add_catalog(&mut self, catalog: Catalog) -> Option<Catalog> {
+        self.catalogs.insert(catalog.locale.clone(), catalog)
+    }
+
+    /// Currently selected locale.
+    pub fn locale(&self) -> Option<&String> {
+        self.locale.as_ref()
+    }
+
+    /// Select locale. Fails if there is no catalog for this locale.
+    pub fn set_locale(&mut self, locale: &str) -> bool {
+        if self.catalogs.contains_key(locale) {
+            self.locale = Some(locale.to_owned());
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Catalog of the currently selected locale.
+    pub fn catalog(&self) -> Option<&Catalog> {
+        self.catalogs.get(self.locale.as_ref()?)
+    }
+
+    /// Translate message using catalog of the current locale.
+    pub fn translate(&self, key: &str, args: &Args) -> Option<String> {
+        self.catalog()?.translate(key, args)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::i18n::{Catalog, Args, format};
+
+    #[test]
+    fn format_args() {
+        let mut args = Args::new();
+        args.insert("name".to_string(), "Max".to_string());
+        assert_eq!(format("Hi, {name}! {missing}", &args), "Hi, Max! {missing}");
+        assert_eq!(format("Unclosed {name", &args), "Unclosed {name");
+    }
+
+    #[test]
+    fn catalog_plural() {
+        let json = r#"{
+            "title": "Inbox",
+            "messages": { "one": "{count} message", "other": "{count} messages" }
+        }"#;
+        let catalog = Catalog::from_json("en".to_string(), json).unwrap();
+        assert_eq!(catalog.translate("title", &Args::new()).unwrap(), "Inbox");
+
+        let mut args = Args::new();
+        args.insert("count".to_string(), "1".to_string());
+        assert_eq!(catalog.translate("messages", &args).unwrap(), "1 message");
+        args.insert("count".to_string(), "5".to_string());
+        assert_eq!(catalog.translate("messages", &args).unwrap(), "5 messages");
+        assert!(catalog.translate("absent", &args).is_none());
+    }
+
+    #[test]
+    fn catalog_invalid() {
+        assert!(Catalog::from_json("en".to_string(), "[]").is_err());
+        assert!(Catalog::from_json("en".to_string(), r#"{"a": {"lots": "x"}}"#).is_err());
+    }
+}
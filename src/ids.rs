@@ -0,0 +1,219 @@
+use std::collections::{HashMap, HashSet};
+use rsgen::{OutputCharsType, gen_random_string};
+
+/// Prefix of the random and seeded names.
+pub const GENERATED_PREFIX: &'static str = "autogen";
+
+/// Length of the random and seeded names including the prefix.
+pub const GENERATED_LEN: usize = 15;
+
+/// Prefix of the counter names if no other prefix was given.
+pub const DEFAULT_COUNTER_PREFIX: &'static str = "uitaco";
+
+/// Way in which names of the elements of new instances are generated.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum IdStrategy {
+
+    /// Random names like `autogenX7f2kQ1a`. This is the default.
+    Random,
+
+    /// Prefix and the number that grows with each name: `uitaco1`, `uitaco2`.
+    Counter(String),
+
+    /// Names that look random but are the same for the same seed and the order of instances.
+    Seeded(u64),
+
+    /// Readable names made of the class name, the number of the instance of that class and
+    /// the original ID: `todoList-3` for the instance root and `todoList-3-title` for it's
+    /// elements.
+    Path,
+}
+
+/// Generator of element names that follows the strategy and never gives the same name twice.
+#[derive(Clone, Debug)]
+pub struct IdGenerator {
+    strategy: IdStrategy,
+
+    counter: u64,
+
+    /// State of the pseudo-random generator of the seeded strategy.
+    seed: u64,
+
+    /// Number of instances of each class created by the path strategy.
+    instances: HashMap<String, usize>,
+
+    /// Names that were given or are known to be taken on the page.
+    used: HashSet<String>,
+}
+
+impl IdStrategy {
+
+    /// Counter strategy with default prefix.
+    pub fn counter() -> Self {
+        IdStrategy::Counter(DEFAULT_COUNTER_PREFIX.to_owned())
+    }
+}
+
+impl Default for IdStrategy {
+
+    fn default() -> Self {
+        IdStrategy::Random
+    }
+}
+
+impl Default for IdGenerator {
+
+    fn default() -> Self {
+        Self::new(Default::default())
+    }
+}
+
+impl IdGenerator {
+
+    pub fn new(strategy: IdStrategy) -> Self {
+        let seed = if let IdStrategy::Seeded(seed) = strategy {
+            seed
+        } else {
+            0
+        };
+        IdGenerator {
+            strategy,
+            counter: 0,
+            seed,
+            instances: Default::default(),
+            used: Default::default(),
+        }
+    }
+
+    pub fn strategy(&self) -> &IdStrategy {
+        &self.strategy
+    }
+
+    /// Change the strategy. Names that were given stay reserved.
+    pub fn set_strategy(&mut self, strategy: IdStrategy) {
+        if let IdStrategy::Seeded(seed) = strategy {
+            self.seed = seed;
+        }
+        self.strategy = strategy;
+    }
+
+    /// Generate names for the elements of new instance of the class. Original IDs are given
+    /// with the ID of the root element first. Get names in the same order.
+    pub fn instance(&mut self, class: &str, origins: &[&str]) -> Vec<String> {
+        let path = if let IdStrategy::Path = self.strategy {
+            let n = self.instances.entry(class.to_owned()).or_insert(0);
+            *n += 1;
+            Some(format!("{}-{}", class, n))
+        } else {
+            None
+        };
+
+        origins.iter().map(|origin| {
+            let name = if let Some(path) = &path {
+                if *origin == class {
+                    path.to_owned()
+                } else {
+                    format!("{}-{}", path, origin)
+                }
+            } else {
+                self.next()
+            };
+            self.unique(name)
+        }).collect()
+    }
+
+    /// Generate name for single element which is not a part of class instance.
+    pub fn name(&mut self) -> String {
+        let name = self.next();
+        self.unique(name)
+    }
+
+    /// Generate next name which is not bound to any class.
+    fn next(&mut self) -> String {
+        match self.strategy.clone() {
+            IdStrategy::Random | IdStrategy::Path => {
+                let oct = OutputCharsType::LatinAlphabetAndNumeric {
+                    use_lower_case: true,
+                    use_upper_case: true,
+                };
+                let random = gen_random_string(GENERATED_LEN - GENERATED_PREFIX.len(), oct);
+                format!("{}{}", GENERATED_PREFIX, random)
+            },
+            IdStrategy::Counter(prefix) => {
+                self.counter += 1;
+                format!("{}{}", prefix, self.counter)
+            },
+            IdStrategy::Seeded(_) => {
+                const CHARS: &'static [u8] =
+                    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+                let mut s = String::from(GENERATED_PREFIX);
+                for _ in GENERATED_PREFIX.len()..GENERATED_LEN {
+                    let n = self.next_seeded();
+                    s.push(CHARS[(n % CHARS.len() as u64) as usize] as char);
+                }
+                s
+            },
+        }
+    }
+
+    /// Next value of the xorshift generator.
+    fn next_seeded(&mut self) -> u64 {
+        // Zero state would give zeros forever.
+        let mut x = if self.seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { self.seed };
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.seed = x;
+        x
+    }
+
+    /// Make the name unique by adding a number to it if it is taken and reserve it.
+    fn unique(&mut self, name: String) -> String {
+        let mut result = name.clone();
+        let mut n = 1;
+        while self.used.contains(&result) {
+            n += 1;
+            result = format!("{}-{}", name, n);
+        }
+        self.used.insert(result.clone());
+        result
+    }
+
+    /// Mark the name as taken so it is never generated.
+    pub fn reserve(&mut self, name: String) {
+        self.used.insert(name);
+    }
+
+    /// Allow the name to be given again.
+    pub fn release(&mut self, name: &str) {
+        self.used.remove(name);
+    }
+
+    /// Whether the name was given or reserved.
+    pub fn is_used(&self, name: &str) -> bool {
+        self.used.contains(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ids::{IdGenerator, IdStrategy};
+
+    #[test]
+    fn deterministic_names() {
+        let mut gen = IdGenerator::new(IdStrategy::Path);
+        gen.reserve("todoList-2".to_string());
+        assert_eq!(gen.instance("todoList", &["todoList", "title"]),
+            vec!["todoList-1", "todoList-1-title"]);
+        assert_eq!(gen.instance("todoList", &["todoList", "title"]),
+            vec!["todoList-2-2", "todoList-2-title"]);
+
+        let mut gen = IdGenerator::new(IdStrategy::Counter("x".to_string()));
+        assert_eq!(gen.instance("card", &["card", "title"]), vec!["x1", "x2"]);
+
+        let a = IdGenerator::new(IdStrategy::Seeded(7)).instance("card", &["card", "title"]);
+        let b = IdGenerator::new(IdStrategy::Seeded(7)).instance("card", &["card", "title"]);
+        assert_eq!(a, b);
+        assert_ne!(a[0], a[1]);
+    }
+}
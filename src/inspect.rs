@@ -0,0 +1,224 @@
+use std::collections::{BTreeMap, HashMap};
+use serde_derive::Serialize;
+use serde_json::Value;
+use crate::ViewWrap;
+use crate::component::{Component, ComponentHandle, ComponentId, COMPONENT_MARK};
+
+/// State of the component and all it's sub-components at the moment of inspection.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ComponentInfo {
+
+    /// ID of the component in the view.
+    pub id: ComponentId,
+
+    /// Name of the class of the component.
+    pub class: String,
+
+    /// Generated name of the component.
+    pub name: String,
+
+    /// Original IDs of the registered elements mapped to their state.
+    pub elements: BTreeMap<String, ElementInfo>,
+
+    /// Callbacks registered by the elements of the component.
+    pub callbacks: Vec<CallbackInfo>,
+
+    /// Anchor of the parent to which this component is mounted.
+    pub anchor: Option<String>,
+
+    /// Sub-components ordered by their IDs.
+    pub components: Vec<ComponentInfo>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct ElementInfo {
+
+    /// Generated ID of the element.
+    pub id: String,
+
+    /// Tag name of the element.
+    pub tag: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct CallbackInfo {
+
+    /// Descriptor of the callback.
+    pub descriptor: usize,
+
+    /// Generated ID of the element which registered the callback.
+    pub owner: String,
+}
+
+impl ComponentInfo {
+
+    /// Inspect the component of the view and all it's sub-components.
+    pub fn of(view: &ViewWrap, handle: &ComponentHandle) -> Self {
+        let owners = {
+            let view = view.inner.view.read().unwrap();
+            view.callback_owners.clone()
+        };
+        Self::of_handle(handle, &owners)
+    }
+
+    fn of_handle(handle: &ComponentHandle, owners: &HashMap<usize, String>) -> Self {
+        let guard = handle.read();
+        let component: &dyn Component = &**guard;
+
+        let elements: BTreeMap<String, ElementInfo> = component.elements().iter()
+            .map(|(origin, e)| (origin.to_owned(), ElementInfo {
+                id: e.id().to_owned(),
+                tag: e.tag_name().name().to_owned(),
+            }))
+            .collect();
+
+        let mut callbacks: Vec<CallbackInfo> = owners.iter()
+            .filter(|(_, owner)| {
+                *owner == component.name() || elements.values().any(|e| &e.id == *owner)
+            })
+            .map(|(descriptor, owner)| CallbackInfo {
+                descriptor: *descriptor,
+                owner: owner.to_owned(),
+            })
+            .collect();
+        callbacks.sort_by_key(|c| c.descriptor);
+
+        let mut handles: Vec<&ComponentHandle> = component.components().iter().collect();
+        handles.sort_by_key(|h| h.id());
+        let components = handles.into_iter()
+            .map(|h| {
+                let mut info = Self::of_handle(h, owners);
+                info.anchor = component.mounted_anchor(h).map(|(a, _)| a);
+                info
+            })
+            .collect();
+
+        ComponentInfo {
+            id: handle.id(),
+            class: component.class().name().to_owned(),
+            name: component.name().to_owned(),
+            elements,
+            callbacks,
+            anchor: None,
+            components,
+        }
+    }
+
+    /// Generated names of this component and all it's sub-components mapped to their
+    /// class names.
+    pub fn classes(&self) -> HashMap<String, String> {
+        let mut map = HashMap::new();
+        self.collect_classes(&mut map);
+        map
+    }
+
+    fn collect_classes(&self, map: &mut HashMap<String, String>) {
+        map.insert(self.name.to_owned(), self.class.to_owned());
+        for c in &self.components {
+            c.collect_classes(map);
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+}
+
+/// JS code that shows the overlay which outlines the component under the mouse pointer
+/// and shows it's class and ID. Classes of the components are given by their generated
+/// names. Running it again only adds the classes.
+pub fn overlay_js(classes: &HashMap<String, String>) -> String {
+    let classes: serde_json::Map<String, Value> = classes.iter()
+        .map(|(k, v)| (k.to_owned(), Value::String(v.to_owned())))
+        .collect();
+    format!("\
+        (function(classes) {{\
+            var s = window.uitacoInspector;\
+            if (!s) {{\
+                s = window.uitacoInspector = {{ classes: {{}} }};\
+                var box = document.createElement('div');\
+                box.style.cssText = 'position: fixed; pointer-events: none; display: none;\
+                    border: 2px solid #e0457b; z-index: 2147483647; box-sizing: border-box;';\
+                var label = document.createElement('div');\
+                label.style.cssText = 'position: absolute; left: -2px; bottom: 100%;\
+                    background: #e0457b; color: #fff; font: 11px monospace;\
+                    padding: 1px 4px; white-space: nowrap;';\
+                box.appendChild(label);\
+                document.body.appendChild(box);\
+                s.box = box;\
+                s.over = function(e) {{\
+                    var el = e.target;\
+                    while (el && el !== document.body\
+                            && !(el.classList && el.classList.contains('{mark}'))) {{\
+                        el = el.parentElement;\
+                    }}\
+                    if (!el || el === document.body) {{\
+                        box.style.display = 'none';\
+                        return;\
+                    }}\
+                    var r = el.getBoundingClientRect();\
+                    box.style.left = r.left + 'px';\
+                    box.style.top = r.top + 'px';\
+                    box.style.width = r.width + 'px';\
+                    box.style.height = r.height + 'px';\
+                    label.textContent = (s.classes[el.id] || '?') + ' #' + el.id;\
+                    box.style.display = 'block';\
+                }};\
+                s.out = function() {{\
+                    box.style.display = 'none';\
+                }};\
+                document.addEventListener('mouseover', s.over);\
+                document.documentElement.addEventListener('mouseleave', s.out);\
+            }}\
+            for (var k in classes) {{\
+                s.classes[k] = classes[k];\
+            }}\
+        }})({classes});\
+    ", mark = COMPONENT_MARK, classes = Value::Object(classes))
+}
+
+/// JS code that removes the overlay.
+pub fn remove_overlay_js() -> String {
+    String::from("\
+        var s = window.uitacoInspector;\
+        if (s) {\
+            document.removeEventListener('mouseover', s.over);\
+            document.documentElement.removeEventListener('mouseleave', s.out);\
+            s.box.remove();\
+            window.uitacoInspector = null;\
+        }\
+    ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::inspect::{ComponentInfo, ElementInfo};
+
+    #[test]
+    fn info_classes() {
+        let leaf = |id, class: &str, name: &str| ComponentInfo {
+            id,
+            class: class.to_string(),
+            name: name.to_string(),
+            elements: BTreeMap::new(),
+            callbacks: Vec::new(),
+            anchor: None,
+            components: Vec::new(),
+        };
+        let mut info = leaf(1, "list", "x1");
+        info.elements.insert("title".to_string(), ElementInfo {
+            id: "x2".to_string(),
+            tag: "h4".to_string(),
+        });
+        info.components.push(leaf(2, "item", "x3"));
+
+        let classes = info.classes();
+        assert_eq!(classes.get("x1").unwrap(), "list");
+        assert_eq!(classes.get("x3").unwrap(), "item");
+
+        let json: serde_json::Value = serde_json::from_str(&info.to_json()).unwrap();
+        assert_eq!(json["elements"]["title"]["tag"], "h4");
+        assert_eq!(json["components"][0]["anchor"], serde_json::Value::Null);
+    }
+}
@@ -28,7 +28,15 @@ use htmldom_read::Node;
 use std::fmt::{Debug, Formatter};
 pub use owning_ref::{RwLockReadGuardRef, RwLockWriteGuardRefMut};
 use std::thread;
-use std::thread::JoinHandle;
+use std::thread::{JoinHandle, ThreadId};
+use crate::i18n::{Translations, Catalog, Args};
+use crate::registry::ClassRegistry;
+use crate::component::InstanceBuilder;
+use crate::component::MountError;
+use crate::ids::{IdGenerator, IdStrategy};
+use crate::query::SelectorError;
+use crate::inspect::ComponentInfo;
+use crate::emit::{ComponentEvent, HandlerId};
 
 /// Components allow to build user interface using repeated patterns with binding to elements.
 /// This allows to speed up building of UI. Binding allows to easily access contents from Rust.
@@ -40,6 +48,57 @@ pub mod tags;
 /// Events that can be generated by tags.
 pub mod events;
 
+/// Translation catalogs for the text of the components.
+pub mod i18n;
+
+/// Accessibility roles, states and helpers for screen readers and keyboard navigation.
+pub mod aria;
+
+/// Property interpolation and directives of component class templates.
+pub mod template;
+
+/// Registry of component classes loaded from many files under namespaces.
+pub mod registry;
+
+/// Build script support for embedding and validating component HTML at compile time.
+pub mod embed;
+
+/// Observable values that keep bound elements in sync with Rust state.
+pub mod observable;
+
+/// Two-way binding of component form controls to serde structs.
+pub mod form;
+
+/// Keyed lists of components rendered from vectors of items.
+pub mod list;
+
+/// Diffing of component HTML into minimal patches of the page.
+pub mod diff;
+
+/// Static HTML rendering of component trees.
+pub mod render;
+
+/// Snapshots of live components that can be saved and restored in another view.
+pub mod snapshot;
+
+/// Strategies of generating names for the elements of class instances.
+pub mod ids;
+
+/// Styles declared in component classes that only apply to the instances of those classes.
+pub mod style;
+
+/// Checks of class HTML that report problems with their positions in the source.
+pub mod lint;
+
+/// CSS selector queries over generated HTML of components and over the page.
+pub mod query;
+
+/// Inspection of the component tree and the overlay that outlines components on the page.
+pub mod inspect;
+
+/// Custom typed events that components emit and which bubble up to their containers.
+pub mod emit;
+
 /// Allows to format JS-strings prefixing quote signs if present with `\`.
 /// For example string `elementById("")` will be transformed to `elementById(\"\")`.
 pub fn js_prefix_quotes(s: &str) -> String {
@@ -118,12 +177,49 @@ pub struct View {
     next_callback_id: CallbackId,
     callbacks: HashMap<CallbackId, &'static dyn Fn(ViewWrap, String)>,
 
+    /// IDs of the elements which registered the callbacks.
+    callback_owners: HashMap<CallbackId, String>,
+
     next_request_id: RequestId,
     requests: HashMap<RequestId, mpsc::Sender<ResponseValue>>,
 
+    /// IDs of the elements about which the requests were made.
+    request_owners: HashMap<RequestId, String>,
+
+    translations: Translations,
+
+    class_registry: Option<Arc<ClassRegistry>>,
+
+    /// Whether page of the view has loaded.
+    ready: bool,
+
+    /// JS code collected by the batches in progress on each thread and nesting depth of
+    /// those batches.
+    batches: HashMap<ThreadId, (String, usize)>,
+
+    /// Styles injected to the view in the order they were injected.
+    css: Vec<String>,
+
+    /// Generator of element names for new instances.
+    ids: IdGenerator,
+
+    /// Full names of the classes which styles were injected.
+    class_styles: HashSet<String>,
+
+    /// Whether the overlay that outlines components on the page is shown.
+    debug_overlay: bool,
+
+    /// Handlers of custom events of the components.
+    event_handlers: emit::Handlers,
+
     thread: Option<JoinHandle<()>>,
 }
 
+/// Ends the batch of the current thread when dropped, even if the batch function panics.
+struct BatchGuard<'a> {
+    view: &'a ViewWrap,
+}
+
 /// Wrap over view handle to make access easier.
 #[derive(Clone, Debug)]
 pub struct ViewWrap {
@@ -148,6 +244,7 @@ struct RequestBuilder {
     view: ViewWrap,
     id: RequestId,
     js: Option<String>,
+    owner: Option<String>,
     rx: mpsc::Receiver<ResponseValue>,
     tx: mpsc::Sender<ResponseValue>,
 }
@@ -180,6 +277,10 @@ impl Debug for View {
 
             next_request_id: RequestId,
             requests: &'a HashMap<RequestId, mpsc::Sender<ResponseValue>>,
+
+            translations: &'a Translations,
+
+            class_registry: &'a Option<Arc<ClassRegistry>>,
         };
 
         let callbacks = {
@@ -204,6 +305,10 @@ impl Debug for View {
 
             next_request_id: self.next_request_id,
             requests: &self.requests,
+
+            translations: &self.translations,
+
+            class_registry: &self.class_registry,
         };
 
         s.fmt(fmt)
@@ -248,53 +353,8 @@ impl View {
 
         my_builder.content = Some(Content::Html(content.clone()));
 
-        let (tx, rx) = mpsc::channel();
-        let view = View {
-            id: 0,
-
-            this: None,
-
-            next_component_id: 0,
-            components: Default::default(),
-
-            next_request_id: 0,
-            requests: Default::default(),
-
-            next_callback_id: 0,
-            callbacks: Default::default(),
-
-            thread: None,
-        };
-        let tuple = ViewTuple {
-            view: RwLock::new(view),
-            sender: Mutex::new(tx),
-        };
-
-        // Create arcs for wrap and access from new webview thread.
-        let tuple = Arc::new(tuple);
-
-        { // Save self-pointer.
-            let mut view = tuple.view.write().unwrap();
-            view.this = Some(Arc::downgrade(&tuple));
-        }
-
-        let wrap = ViewWrap {
-            inner: tuple.clone(),
-        };
-
-        // Create and add root component.
-        let mut classes = Class::all_from_html(&content);
-        let body_class = classes.remove(uitaco_body_id).unwrap();
-        let mut body_builder = body_class.into_builder();
-        body_builder.element_by_id_mut(uitaco_body_id).unwrap().use_initial_name();
-        let body_component = body_builder.build(wrap.clone());
-        let root_component = RootComponent { base: body_component };
-        {
-            let mut guard = wrap.inner.view.write().unwrap();
-            let data = RwLock::new(Box::new(root_component) as _);
-            guard.components.insert(ROOT_COMPONENT_ID, Arc::new(data));
-            guard.next_component_id += 1;
-        }
+        let (wrap, rx) = Self::new_detached(&content, uitaco_body_id);
+        let tuple = wrap.inner.clone();
 
         // Thread where WebView will live.
         let arc2 = tuple.clone();
@@ -374,6 +434,117 @@ impl View {
         });
 
         wrap.inner.view.write().unwrap().thread = Some(thread);
+
+        // Notify back-end when page is loaded.
+        wrap.eval(String::from("\
+            var ready = function() {\
+                window.external.invoke(JSON.stringify({ incmd: 'ready' }));\
+            };\
+            if (document.readyState === 'complete') {\
+                ready();\
+            } else {\
+                window.addEventListener('load', ready);\
+            }\
+        "));
+        wrap
+    }
+
+    /// Create view with root component that is not connected to any window yet. Get receiver
+    /// of the commands sent to the view.
+    fn new_detached(content: &str, uitaco_body_id: &str)
+            -> (ViewWrap, mpsc::Receiver<ViewCmd>) {
+        let (tx, rx) = mpsc::channel();
+        let view = View {
+            id: 0,
+
+            this: None,
+
+            next_component_id: 0,
+            components: Default::default(),
+
+            next_request_id: 0,
+            requests: Default::default(),
+
+            next_callback_id: 0,
+            callbacks: Default::default(),
+            callback_owners: Default::default(),
+
+            request_owners: Default::default(),
+
+            translations: Default::default(),
+
+            class_registry: None,
+
+            ready: false,
+
+            batches: Default::default(),
+
+            css: Default::default(),
+
+            ids: Default::default(),
+
+            class_styles: Default::default(),
+
+            debug_overlay: false,
+
+            event_handlers: Default::default(),
+
+            thread: None,
+        };
+        let tuple = ViewTuple {
+            view: RwLock::new(view),
+            sender: Mutex::new(tx),
+        };
+
+        // Create arcs for wrap and access from new webview thread.
+        let tuple = Arc::new(tuple);
+
+        { // Save self-pointer.
+            let mut view = tuple.view.write().unwrap();
+            view.this = Some(Arc::downgrade(&tuple));
+        }
+
+        let wrap = ViewWrap {
+            inner: tuple.clone(),
+        };
+
+        // Create and add root component.
+        let mut classes = Class::all_from_html(&content);
+        let body_class = classes.remove(uitaco_body_id).unwrap();
+        let mut body_builder = body_class.into_builder();
+        body_builder.element_by_id_mut(uitaco_body_id).unwrap().use_initial_name();
+        let body_component = body_builder.build(wrap.clone());
+        let root_component = RootComponent { base: body_component };
+        {
+            let mut guard = wrap.inner.view.write().unwrap();
+            let data = RwLock::new(Box::new(root_component) as _);
+            guard.components.insert(ROOT_COMPONENT_ID, Arc::new(data));
+            guard.next_component_id += 1;
+        }
+
+        (wrap, rx)
+    }
+
+    /// Create view that has no window. Components can be built, changed and rendered to
+    /// static HTML but JS code is never run and requests to the page get no response.
+    pub fn new_headless() -> ViewWrap {
+        let uitaco_body_id = "uitacoBody";
+        let content = format!("<html><head><title></title></head>\
+            <body class=\"{}\" id=\"{}\"></body></html>",
+            component::COMPONENT_MARK, uitaco_body_id);
+        let (wrap, rx) = Self::new_detached(&content, uitaco_body_id);
+
+        thread::spawn(move || {
+            for cmd in rx {
+                match cmd {
+                    ViewCmd::Eval(sender, _) => if let Some(sender) = sender {
+                        sender.send(Err(web_view::Error::Dispatch)).unwrap_or(());
+                    },
+                    ViewCmd::InjectCss(_) => (),
+                    ViewCmd::Exit => break,
+                }
+            }
+        });
         wrap
     }
 
@@ -393,6 +564,12 @@ impl View {
             let id = self.next_component_id;
             self.next_component_id += 1;
 
+            if self.debug_overlay {
+                let mut classes = HashMap::new();
+                classes.insert(component.name().to_owned(), component.class().name().to_owned());
+                self.handle().eval(inspect::overlay_js(&classes));
+            }
+
             let arc = Arc::new(RwLock::new(component));
             self.components.insert(id, arc.clone());
             (id, arc)
@@ -423,10 +600,15 @@ impl View {
         RequestBuilder::new(self.handle(), id)
     }
 
-    /// Add new callback. Get descriptor of newly registered callback.
-    fn add_callback(&mut self, f: Box<&'static Callback>) -> CallbackId {
+    /// Add new callback for the element with given ID.
+    /// Get descriptor of newly registered callback.
+    fn add_callback(&mut self, f: Box<&'static Callback>, owner: &str) -> CallbackId {
         let id = self.next_callback_id;
         self.callbacks.insert(id, *f);
+        // Unnamed elements share empty ID so they cannot own callbacks.
+        if !owner.is_empty() {
+            self.callback_owners.insert(id, owner.to_owned());
+        }
         self.next_callback_id += 1;
         id
     }
@@ -436,9 +618,32 @@ impl View {
     /// # Panics
     /// This function will panic if callback is not present.
     fn remove_callback<'a, 'b>(&'a mut self, id: CallbackId) -> &'b Callback {
+        self.callback_owners.remove(&id);
         self.callbacks.remove(&id).unwrap()
     }
 
+    /// Remove all callbacks and pending requests of the elements with given IDs.
+    /// Waiters of removed requests receive no response. Get descriptors of removed callbacks.
+    fn remove_owned_by(&mut self, owners: &HashSet<String>) -> Vec<CallbackId> {
+        let callbacks: Vec<CallbackId> = self.callback_owners.iter()
+            .filter(|(_, owner)| owners.contains(*owner))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &callbacks {
+            self.remove_callback(*id);
+        }
+
+        let requests: Vec<RequestId> = self.request_owners.iter()
+            .filter(|(_, owner)| owners.contains(*owner))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in requests {
+            self.remove_request(id);
+        }
+
+        callbacks
+    }
+
     /// Find callback with given id.
     fn callback<'a, 'b>(&'a self, id: CallbackId) -> Option<Box<&'b Callback>> {
         if let Some(f) = self.callbacks.get(&id) {
@@ -476,6 +681,16 @@ impl View {
             } => {
                 self.respond(request, ResponseValue::Str(value));
             },
+
+            Ready => {
+                if !self.ready {
+                    self.ready = true;
+
+                    // Hooks need access to the view which is locked by now.
+                    let wrap = self.handle();
+                    thread::spawn(move || wrap.notify_view_ready());
+                }
+            },
         }
 
         Ok(())
@@ -484,12 +699,13 @@ impl View {
     /// Remove previously registered request by id if any. Function returns a sender
     /// that was to be used to wake up the waiting function.
     fn remove_request(&mut self, id: RequestId) -> Option<mpsc::Sender<ResponseValue>> {
+        self.request_owners.remove(&id);
         self.requests.remove(&id)
     }
 
     /// Save request response. Remove request from waiting list and wake up the waiter.
     fn respond(&mut self, id: RequestId, val: ResponseValue) {
-        if let Some(r) = self.requests.remove(&id) {
+        if let Some(r) = self.remove_request(id) {
             let _result = r.send(val);
             // TODO use result
         }
@@ -534,10 +750,264 @@ impl ViewWrap {
         view.root_component()
     }
 
-    /// Add new callback. Get descriptor of newly registered callback.
-    fn add_callback(&mut self, f: Box<&'static Callback>) -> CallbackId {
+    /// Whether page of the view has loaded.
+    pub fn is_ready(&self) -> bool {
+        let view = self.inner.view.read().unwrap();
+        view.ready
+    }
+
+    /// Run view ready hooks of all components in the order they were registered.
+    fn notify_view_ready(&self) {
+        let mut components: Vec<(ComponentId, Arc<RwLock<Box<dyn Component>>>)> = {
+            let view = self.inner.view.read().unwrap();
+            view.components.iter().map(|(id, c)| (*id, c.clone())).collect()
+        };
+        components.sort_by_key(|(id, _)| *id);
+
+        for (_, component) in components {
+            component.write().unwrap().on_view_ready();
+        }
+    }
+
+    /// Attach class registry to the view so components can be created by class name.
+    /// Classes of the registry also can be referenced by class HTML of any instance
+    /// built for this view.
+    pub fn set_class_registry(&self, registry: Arc<ClassRegistry>) {
+        let mut view = self.inner.view.write().unwrap();
+        view.class_registry = Some(registry);
+    }
+
+    /// Class registry attached to the view.
+    pub fn class_registry(&self) -> Option<Arc<ClassRegistry>> {
+        let view = self.inner.view.read().unwrap();
+        view.class_registry.clone()
+    }
+
+    /// Set the way in which names of the elements of new instances are generated.
+    /// Names that were already given stay reserved.
+    pub fn set_id_strategy(&self, strategy: IdStrategy) {
+        let mut view = self.inner.view.write().unwrap();
+        view.ids.set_strategy(strategy);
+    }
+
+    pub fn id_strategy(&self) -> IdStrategy {
+        let view = self.inner.view.read().unwrap();
+        view.ids.strategy().clone()
+    }
+
+    /// Reserve all IDs that are present on the page so generated names never collide
+    /// with them. Get number of found IDs.
+    pub fn reserve_page_ids(&self) -> usize {
+        let req = self.clone().new_request();
+        let js = format!("\
+            var list = document.querySelectorAll('[id]');\
+            var ids = [];\
+            for (var i = 0; i < list.length; i++) {{\
+                ids.push(list[i].id);\
+            }}\
+            window.external.invoke(JSON.stringify({{\
+                incmd: 'attribute',\
+                request: {},\
+                value: ids.join(' ')\
+            }}));\
+        ", req.id());
+        let response = req.run(js).recv();
+        let ids = if let Ok(ResponseValue::Str(s)) = response {
+            s
+        } else {
+            return 0;
+        };
+
+        let mut view = self.inner.view.write().unwrap();
+        let mut count = 0;
+        for id in ids.split_whitespace() {
+            view.ids.reserve(id.to_owned());
+            count += 1;
+        }
+        count
+    }
+
+    /// Inspect the whole component tree starting from the root component.
+    pub fn inspect(&self) -> ComponentInfo {
+        ComponentInfo::of(self, &self.root_component())
+    }
+
+    /// Inspect the component and all it's sub-components.
+    pub fn inspect_component(&self, component: &ComponentHandle) -> ComponentInfo {
+        ComponentInfo::of(self, component)
+    }
+
+    /// Show or hide the overlay that outlines the component under the mouse pointer and
+    /// shows it's class and ID.
+    pub fn set_debug_overlay(&self, enabled: bool) {
+        let components: Vec<Arc<RwLock<Box<dyn Component>>>> = {
+            let mut view = self.inner.view.write().unwrap();
+            if view.debug_overlay == enabled {
+                return;
+            }
+            view.debug_overlay = enabled;
+            view.components.values().cloned().collect()
+        };
+
+        if enabled {
+            let classes = components.iter()
+                .map(|c| {
+                    let c = c.read().unwrap();
+                    (c.name().to_owned(), c.class().name().to_owned())
+                })
+                .collect();
+            self.eval(inspect::overlay_js(&classes));
+        } else {
+            self.eval(inspect::remove_overlay_js());
+        }
+    }
+
+    /// Whether the overlay that outlines components on the page is shown.
+    pub fn is_debug_overlay(&self) -> bool {
+        let view = self.inner.view.read().unwrap();
+        view.debug_overlay
+    }
+
+    /// Generate name for an element with the ID strategy of the view.
+    pub fn generate_id(&self) -> String {
+        let mut view = self.inner.view.write().unwrap();
+        view.ids.name()
+    }
+
+    /// Find elements on the page that match CSS selector. Any selector supported by the page
+    /// can be used. Elements that have no ID get a generated one on the page, generated HTML
+    /// of components is not changed.
+    pub fn query_live(&self, selector: &str) -> Result<Vec<Box<dyn Element>>, SelectorError> {
+        let req = self.clone().new_request();
+        let request = req.id();
+        let js = query::live_js(selector, request);
+        let response = req.run(js).recv();
+        let found: Vec<(String, String)> = if let Ok(ResponseValue::Str(s)) = response {
+            let found: Option<Vec<(String, String)>> = serde_json::from_str(&s)
+                .map_err(|_| SelectorError::Page)?;
+            found.ok_or(SelectorError::Page)?
+        } else {
+            return Err(SelectorError::Page);
+        };
+
+        let mut names = Vec::new();
+        let mut elements = Vec::with_capacity(found.len());
+        for (i, (tag, id)) in found.into_iter().enumerate() {
+            let id = if id.is_empty() {
+                let id = self.generate_id();
+                names.push((i, id.clone()));
+                id
+            } else {
+                id
+            };
+            elements.push(TagName::from(tag.as_str()).new_impl(self.clone(), id));
+        }
+        if !names.is_empty() {
+            self.eval(query::name_js(request, &names));
+        }
+        Ok(elements)
+    }
+
+    /// Create instance builder for the class with given full name from the attached registry.
+    pub fn instance_builder(&self, name: &str) -> Option<InstanceBuilder> {
+        self.class_registry()?.builder(name)
+    }
+
+    /// Build component of the class with given full name from the attached registry.
+    /// All elements of the component get names generated with the ID strategy of the view.
+    /// Classes with required properties or references that cannot be built are not built
+    /// this way.
+    pub fn build_component(&self, name: &str) -> Option<ComponentBase> {
+        let mut builder = self.instance_builder(name)?;
+        builder.use_view_names(self);
+        builder.try_build(self.clone()).ok()
+    }
+
+    /// Handle custom events of type `E` emitted by the component or any of it's
+    /// sub-components. Handlers of the same component run in the order they were added.
+    /// Handlers are removed together with the component.
+    pub fn on_event<E, F>(&self, component: &ComponentHandle, handler: F) -> HandlerId
+            where E: 'static, F: Fn(&ViewWrap, &mut ComponentEvent<E>) + Send + Sync + 'static {
+        let mut view = self.inner.view.write().unwrap();
+        view.event_handlers.add::<E>(component.id(), Arc::new(handler))
+    }
+
+    /// Remove custom event handler. Get whether it was found.
+    pub fn remove_event_handler(&self, id: HandlerId) -> bool {
+        let mut view = self.inner.view.write().unwrap();
+        view.event_handlers.remove(id)
+    }
+
+    /// Emit custom event from the component. Event is given to the handlers of the component
+    /// and then to the handlers of it's containers up to the root component until some
+    /// handler stops propagation. Get the event after all handlers ran to check whether it
+    /// was handled and to read the data changed by handlers.
+    ///
+    /// Path to the component is found by searching the tree from the root, which read-locks
+    /// the containers of the component and any other component visited before it. None of
+    /// them may be write-locked by the caller, the component itself can be.
+    pub fn emit<E: 'static>(&self, component: &ComponentHandle, data: E) -> ComponentEvent<E> {
+        let path = emit::bubble_path(&self.root_component(), component);
+        let mut event = ComponentEvent::new(component.clone(), data);
+        emit::dispatch(self, path, &mut event);
+        event
+    }
+
+    /// Register component in the interface without adding it to any container.
+    /// Handle can be used to project the component into a slot of another instance.
+    pub fn register_component(&self, component: Box<dyn Component>) -> ComponentHandle {
+        let mut view = self.inner.view.write().unwrap();
+        view.add_component(component)
+    }
+
+    /// Remove the component and all of it's sub-components from the interface. Unmount hooks
+    /// are called first and then elements of removed components release their resources.
+    /// Any callbacks and pending requests left after that are removed too. In debug builds
+    /// those leftovers are reported to stderr.
+    ///
+    /// Component must already be removed from it's container.
+    pub fn teardown_component(&self, component: &ComponentHandle) {
+        let children: Vec<ComponentHandle> = {
+            let guard = component.read();
+            guard.components().iter().cloned().collect()
+        };
+        for child in &children {
+            self.teardown_component(child);
+        }
+
+        let owners = {
+            let mut guard = component.lock().write().unwrap();
+            guard.on_unmount();
+            guard.release_elements();
+
+            let mut owners: HashSet<String> = guard.elements().values()
+                .map(|e| e.id().to_owned())
+                .filter(|id| !id.is_empty())
+                .collect();
+            owners.insert(guard.name().to_owned());
+            owners
+        };
+
+        let mut view = self.inner.view.write().unwrap();
+        view.components.remove(&component.id());
+        view.event_handlers.remove_component(component.id());
+        for id in &owners {
+            view.ids.release(id);
+        }
+        let leaked = view.remove_owned_by(&owners);
+        if cfg!(debug_assertions) {
+            for id in leaked {
+                eprintln!("uitaco: callback {} of removed component {} was not released",
+                    id, component.id());
+            }
+        }
+    }
+
+    /// Add new callback for the element with given ID.
+    /// Get descriptor of newly registered callback.
+    fn add_callback(&mut self, f: Box<&'static Callback>, owner: &str) -> CallbackId {
         let mut view = self.inner.view.write().unwrap();
-        view.add_callback(f)
+        view.add_callback(f, owner)
     }
 
     /// Remove previously registered callback.
@@ -562,9 +1032,31 @@ impl ViewWrap {
 
     /// Inject styles to the view.
     pub fn inject_css(&self, css: String) {
+        self.inner.view.write().unwrap().css.push(css.clone());
         self.inner.sender.lock().unwrap().send(ViewCmd::InjectCss(css)).unwrap();
     }
 
+    /// Inject styles of the class if they were not injected yet.
+    fn inject_class_css(&self, class: &str, css: &str) {
+        let first = {
+            let mut view = self.inner.view.write().unwrap();
+            view.class_styles.insert(class.to_owned())
+        };
+        if first {
+            self.inject_css(css.to_owned());
+        }
+    }
+
+    /// All styles injected to the view.
+    pub fn injected_css(&self) -> Vec<String> {
+        self.inner.view.read().unwrap().css.clone()
+    }
+
+    /// Render the whole view into standalone HTML document without running any JS code.
+    pub fn render_static(&self, title: &str) -> String {
+        render::document(&self.root_component(), &self.injected_css(), title)
+    }
+
     /// Run given JS code and wait for result.
     pub fn eval_wait(&self, js: String) -> WVResult {
         let (tx, rx) = mpsc::channel();
@@ -578,6 +1070,144 @@ impl ViewWrap {
         self.inner.sender.lock().unwrap().send(ViewCmd::Eval(None, js)).unwrap();
     }
 
+    /// Announce given text to screen reader users through the hidden live region. Text with
+    /// `Off` politeness is not announced.
+    pub fn announce(&self, text: &str, politeness: aria::Politeness) {
+        if politeness == aria::Politeness::Off {
+            return;
+        }
+        self.eval(aria::announce_js(text, politeness));
+    }
+
+    /// Run given JS code without waiting for result. If batch is in progress on the current
+    /// thread then code is postponed until the batch ends. Code sent from other threads is
+    /// not captured by that batch.
+    pub fn eval_batched(&self, js: String) {
+        {
+            let mut view = self.inner.view.write().unwrap();
+            if let Some((batch, _)) = view.batches.get_mut(&thread::current().id()) {
+                batch.push_str(&js);
+                return;
+            }
+        }
+        self.eval(js);
+    }
+
+    /// Run the function collecting all JS code sent by `eval_batched` from the current thread
+    /// and evaluate it at once when the function returns. Batches can be nested, code is
+    /// evaluated when the outer one ends. If the function panics then collected code is
+    /// still evaluated.
+    pub fn batch<R, F: FnOnce() -> R>(&self, f: F) -> R {
+        {
+            let mut view = self.inner.view.write().unwrap();
+            let batch = view.batches.entry(thread::current().id())
+                .or_insert_with(|| (String::new(), 0));
+            batch.1 += 1;
+        }
+
+        let _guard = BatchGuard { view: self };
+        f()
+    }
+
+    /// Register translation catalog. Catalog of the same locale gets replaced.
+    pub fn add_catalog(&self, catalog: Catalog) -> Option<Catalog> {
+        let mut view = self.inner.view.write().unwrap();
+        view.translations.add_catalog(catalog)
+    }
+
+    /// Currently selected locale.
+    pub fn locale(&self) -> Option<String> {
+        let view = self.inner.view.read().unwrap();
+        view.translations.locale().map(|s| s.to_owned())
+    }
+
+    /// Translate message using catalog of the current locale.
+    pub fn translate(&self, key: &str, args: &Args) -> Option<String> {
+        let view = self.inner.view.read().unwrap();
+        view.translations.translate(key, args)
+    }
+
+    /// Select locale and re-translate all elements of the page and of generated HTML of
+    /// the components that are marked with translation keys. Returns false if there is no
+    /// catalog for this locale. Components must not be locked by the caller.
+    pub fn set_locale(&self, locale: &str) -> bool {
+        let request = {
+            let mut view = self.inner.view.write().unwrap();
+            if !view.translations.set_locale(locale) {
+                return false;
+            }
+            view.new_request()
+        };
+
+        // Generated HTML is used to render components without the page so it is translated
+        // too.
+        let components: Vec<Arc<RwLock<Box<dyn Component>>>> = {
+            let view = self.inner.view.read().unwrap();
+            view.components.values().cloned().collect()
+        };
+        for lock in components {
+            let mut guard = lock.write().unwrap();
+            if let Some(base) = guard.base_mut() {
+                base.translate_html();
+            }
+        }
+
+        // Collect all distinct key and arguments pairs that are used on the page.
+        let js = format!("\
+            var list = [];\
+            var seen = {{}};\
+            var nodes = document.querySelectorAll('[{key}]');\
+            for (var i = 0; i < nodes.length; i++) {{\
+                var k = nodes[i].getAttribute('{key}') + '\\n'\
+                    + (nodes[i].getAttribute('{args}') || '');\
+                if (!seen[k]) {{\
+                    seen[k] = true;\
+                    list.push(k);\
+                }}\
+            }}\
+            window.external.invoke(JSON.stringify({{\
+                incmd: 'attribute',\
+                request: {id},\
+                value: JSON.stringify(list)\
+            }}));\
+        ", key = i18n::I18N_KEY_ATTR, args = i18n::I18N_ARGS_ATTR, id = request.id());
+        let response = request.run(js).recv();
+        let list: Vec<String> = if let Ok(ResponseValue::Str(s)) = response {
+            serde_json::from_str(&s).unwrap_or_default()
+        } else {
+            return true;
+        };
+
+        // Translate them and send the results back to the page.
+        let mut map = serde_json::Map::with_capacity(list.len());
+        {
+            let view = self.inner.view.read().unwrap();
+            for item in list {
+                let mut split = item.splitn(2, '\n');
+                let key = split.next().unwrap();
+                let args = i18n::args_from_json(split.next().unwrap_or(""));
+                if let Some(text) = view.translations.translate(key, &args) {
+                    map.insert(item.clone(), serde_json::Value::String(text));
+                }
+            }
+        }
+        let js = format!("\
+            var map = {map};\
+            var nodes = document.querySelectorAll('[{key}]');\
+            for (var i = 0; i < nodes.length; i++) {{\
+                var k = nodes[i].getAttribute('{key}') + '\\n'\
+                    + (nodes[i].getAttribute('{args}') || '');\
+                if (map.hasOwnProperty(k)) {{\
+                    nodes[i].textContent = map[k];\
+                }}\
+            }}\
+        ", map = serde_json::Value::Object(map), key = i18n::I18N_KEY_ATTR,
+            args = i18n::I18N_ARGS_ATTR);
+        self.eval(js);
+
+        true
+    }
+
     pub fn wait_to_finish(&self) {
         // Do not hold the lock just to wait view to finish as something else may need to
         // acquire the lock until then.
@@ -633,6 +1263,7 @@ impl RequestBuilder {
             tx,
             rx,
             js: None,
+            owner: None,
         }
     }
 
@@ -646,6 +1277,13 @@ impl RequestBuilder {
         self
     }
 
+    /// Set ID of the element this request is about. Request gets dropped when the component
+    /// of this element is removed.
+    pub fn owner(mut self, id: String) -> Self {
+        self.owner = Some(id);
+        self
+    }
+
     /// Evaluate the request.
     pub fn eval(self) -> mpsc::Receiver<ResponseValue> {
         let js = self.js.unwrap();
@@ -657,6 +1295,9 @@ impl RequestBuilder {
             let mut view = view_wrap.inner().view.write().unwrap();
             // Save the sender to the view so callback could send the value to listener.
             view.requests.insert(id, self.tx);
+            if let Some(owner) = self.owner.filter(|o| !o.is_empty()) {
+                view.request_owners.insert(id, owner);
+            }
         }
         let err = {
             // Must be called with unlocked View because it locks the View.
@@ -708,22 +1349,32 @@ impl Container for RootComponent {
             i.innerHTML += '{}';
         ", id, html.to_string());
 
-        let result = self.base.add_component(component);
+        // Generated HTML of the root is kept in sync with the page so it can be rendered.
+        let result = self.base.adopt(component, None);
+        let result = match result {
+            Ok(handle) => Ok(handle),
+            Err(MountError::Add(e)) => Err(e),
+            Err(_) => unreachable!(),
+        };
         if let Err(e) = result {
             return Err(e);
         }
         self.view_mut().eval(js);
-        Ok(result.unwrap())
+        let handle = result.unwrap();
+        handle.mounted();
+        Ok(handle)
     }
 
     fn remove_component(&mut self, component: &ComponentHandle) -> Option<()> {
         let result = self.base.remove_component(component);
         if let Some(_) = result {
+            let name = component.read().as_owner().name().to_owned();
             let js = format!("\
                 var i = document.getElementById('{}');
                 i.outerHTML = '';
-            ", component.read().as_owner().name());
+            ", name);
             self.view_mut().eval(js);
+            self.base.remove_html(&name);
             Some(())
         } else {
             None
@@ -780,6 +1431,41 @@ impl Component for RootComponent {
     fn class(&self) -> &ClassHandle {
         self.base.class()
     }
+
+    fn base(&self) -> Option<&ComponentBase> {
+        Some(&self.base)
+    }
+
+    fn base_mut(&mut self) -> Option<&mut ComponentBase> {
+        Some(&mut self.base)
+    }
+}
+
+impl Drop for BatchGuard<'_> {
+
+    fn drop(&mut self) {
+        let js = {
+            // Lock can be poisoned if the batch function panicked while holding it.
+            let mut view = self.view.inner.view.write().unwrap_or_else(|e| e.into_inner());
+            let id = thread::current().id();
+            let end = if let Some(batch) = view.batches.get_mut(&id) {
+                batch.1 -= 1;
+                batch.1 == 0
+            } else {
+                false
+            };
+            if end {
+                view.batches.remove(&id).map(|(js, _)| js)
+            } else {
+                None
+            }
+        };
+        if let Some(js) = js {
+            if !js.is_empty() {
+                self.view.eval(js);
+            }
+        }
+    }
 }
 
 /// Command that can be received from JavaScript front-end.
@@ -806,6 +1492,9 @@ enum InCmd {
         request: RequestId,
         value: String,
     },
+
+    /// Page of the view has loaded.
+    Ready,
 }
 
 /// Value received from JavaScript front-end.
@@ -813,3 +1502,22 @@ enum ResponseValue {
     Bool(bool),
     Str(String)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::panic::{self, AssertUnwindSafe};
+    use crate::View;
+
+    #[test]
+    fn batch_ends_on_panic() {
+        let view = View::new_headless();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            view.batch(|| {
+                view.eval_batched(String::from("a();"));
+                panic!("batch failed");
+            })
+        }));
+        assert!(result.is_err());
+        assert!(view.inner.view.read().unwrap().batches.is_empty());
+    }
+}
@@ -0,0 +1,565 @@
+use std::collections::HashSet;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+use crate::component::{self, SKIP_ELEMENT_MARK};
+use crate::tags::TagName;
+
+/// Elements that never have closing tag.
+const VOID_TAGS: &'static [&'static str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param",
+    "source", "track", "wbr",
+];
+
+/// Standard HTML, SVG and MathML elements. Tags that are not listed here and have no
+/// specific element implementation are most likely misspelled.
+const HTML_TAGS: &'static [&'static str] = &[
+    "a", "abbr", "address", "area", "article", "aside", "audio", "b", "base", "bdi", "bdo",
+    "blockquote", "body", "br", "button", "canvas", "caption", "cite", "code", "col",
+    "colgroup", "data", "datalist", "dd", "del", "details", "dfn", "dialog", "div", "dl", "dt",
+    "em", "embed", "fieldset", "figcaption", "figure", "footer", "form", "h1", "h2", "h3",
+    "h4", "h5", "h6", "head", "header", "hgroup", "hr", "html", "i", "iframe", "img", "input",
+    "ins", "kbd", "label", "legend", "li", "link", "main", "map", "mark", "menu", "meta",
+    "meter", "nav", "noscript", "object", "ol", "optgroup", "option", "output", "p", "param",
+    "picture", "pre", "progress", "q", "rp", "rt", "ruby", "s", "samp", "script", "search",
+    "section", "select", "slot", "small", "source", "span", "strong", "style", "sub",
+    "summary", "sup", "table", "tbody", "td", "template", "textarea", "tfoot", "th", "thead",
+    "time", "title", "tr", "track", "u", "ul", "var", "video", "wbr",
+    "svg", "g", "defs", "use", "symbol", "path", "rect", "circle", "ellipse", "line",
+    "polyline", "polygon", "text", "tspan", "image", "lineargradient", "radialgradient",
+    "stop", "clippath", "mask", "pattern", "filter", "foreignobject", "math",
+];
+
+/// Elements which content is not HTML.
+const RAW_TEXT_TAGS: &'static [&'static str] = &["script", "style"];
+
+/// Attributes which values are lists of IDs of other elements.
+const ID_REF_ATTRS: &'static [&'static str] = &[
+    "for", "form", "list", "headers", "aria-labelledby", "aria-describedby", "aria-controls",
+    "aria-owns", "aria-activedescendant", "aria-flowto", "aria-details", "aria-errormessage",
+];
+
+/// Position in the source HTML. Lines and columns start from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+
+    /// Byte offset from the start of the source.
+    pub offset: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+
+    /// Class will not work as expected.
+    Error,
+
+    /// Class works but likely not as intended.
+    Warning,
+}
+
+/// Problem found in class HTML.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiagnosticKind {
+
+    /// Element is marked as component but has no ID so class cannot be created.
+    MissingRootId,
+
+    /// Two classes have the same name.
+    DuplicateClass(String),
+
+    /// Two elements of the class have the same ID.
+    DuplicateId(String),
+
+    /// Element with ID has a tag that is neither a standard HTML element nor has specific
+    /// element implementation, so it can only be accessed as `Unknown` element.
+    UnknownTag {
+        id: String,
+        tag: String,
+    },
+
+    /// Element refers to an element which is skipped and is not present in the class.
+    SkippedReference {
+        id: String,
+        attribute: String,
+    },
+
+    /// Tag is closed but was never opened or is never closed.
+    UnbalancedTag(String),
+}
+
+/// Problem found in class HTML with it's position.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub kind: DiagnosticKind,
+
+    /// Name of the class in which problem was found.
+    pub class: Option<String>,
+
+    pub position: Position,
+}
+
+/// Tag found in the source.
+struct Tag {
+    name: String,
+    attrs: Vec<(String, String)>,
+    offset: usize,
+    closing: bool,
+    self_closing: bool,
+}
+
+/// Element that is opened at the current point of the source.
+struct Open {
+    name: String,
+    offset: usize,
+
+    /// Whether this element starts a class.
+    class_root: bool,
+
+    skipped: bool,
+}
+
+/// Data collected for the class until it's root is closed.
+struct ClassScope {
+    name: Option<String>,
+    ids: HashSet<String>,
+    skipped: HashSet<String>,
+
+    /// References to other elements with the ID, attribute and offset of the referencing tag.
+    refs: Vec<(String, String, usize)>,
+}
+
+impl DiagnosticKind {
+
+    pub fn severity(&self) -> Severity {
+        use DiagnosticKind::*;
+        match self {
+            MissingRootId => Severity::Error,
+            DuplicateClass(_) => Severity::Error,
+            DuplicateId(_) => Severity::Error,
+            UnknownTag { .. } => Severity::Warning,
+            SkippedReference { .. } => Severity::Error,
+            UnbalancedTag(_) => Severity::Warning,
+        }
+    }
+}
+
+impl Display for DiagnosticKind {
+
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        use DiagnosticKind::*;
+        match self {
+            MissingRootId => write!(fmt, "component has no ID"),
+            DuplicateClass(name) => write!(fmt, "class `{}` is already defined", name),
+            DuplicateId(id) => write!(fmt, "ID `{}` is used more than once", id),
+            UnknownTag { id, tag } => write!(fmt,
+                "element `{}` has tag `{}` which is not known and is accessed as unknown",
+                id, tag),
+            SkippedReference { id, attribute } =>
+                write!(fmt, "attribute `{}` refers to skipped element `{}`", attribute, id),
+            UnbalancedTag(tag) => write!(fmt, "tag `{}` is not balanced", tag),
+        }
+    }
+}
+
+impl Diagnostic {
+
+    pub fn severity(&self) -> Severity {
+        self.kind.severity()
+    }
+}
+
+impl Display for Diagnostic {
+
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        let severity = match self.severity() {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(fmt, "{}:{}: {}: ", self.position.line, self.position.column, severity)?;
+        if let Some(class) = &self.class {
+            write!(fmt, "class `{}`: ", class)?;
+        }
+        write!(fmt, "{}", self.kind)
+    }
+}
+
+/// Check all classes of the HTML source. Diagnostics are sorted by position.
+pub fn validate(html: &str) -> Vec<Diagnostic> {
+    let lines = line_starts(html);
+    let position = |offset: usize| {
+        let line = match lines.binary_search(&offset) {
+            Ok(i) => i,
+            Err(i) => i - 1,
+        };
+        Position {
+            line: line + 1,
+            column: html[lines[line]..offset].chars().count() + 1,
+            offset,
+        }
+    };
+
+    let mut diagnostics = Vec::new();
+    let mut push = |kind, class: Option<&String>, offset| {
+        diagnostics.push(Diagnostic {
+            kind,
+            class: class.cloned(),
+            position: position(offset),
+        });
+    };
+
+    let mut stack: Vec<Open> = Vec::new();
+    let mut classes: Vec<ClassScope> = Vec::new();
+    let mut class_names = HashSet::new();
+    for tag in tags(html) {
+        if tag.closing {
+            let found = stack.iter().rposition(|o| o.name == tag.name);
+            let found = if let Some(found) = found {
+                found
+            } else {
+                push(DiagnosticKind::UnbalancedTag(tag.name), None, tag.offset);
+                continue;
+            };
+            while stack.len() > found {
+                let open = stack.pop().unwrap();
+                if stack.len() > found {
+                    push(DiagnosticKind::UnbalancedTag(open.name.clone()), None, open.offset);
+                }
+                if open.class_root {
+                    let scope = classes.pop().unwrap();
+                    for (id, attribute, offset) in scope.refs {
+                        if scope.skipped.contains(&id) && !scope.ids.contains(&id) {
+                            push(DiagnosticKind::SkippedReference { id, attribute },
+                                scope.name.as_ref(), offset);
+                        }
+                    }
+                }
+            }
+            continue;
+        }
+
+        let attr = |name: &str| tag.attrs.iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.as_str());
+        let class_attr = attr("class").unwrap_or("");
+        let id = attr("id").map(|s| s.to_owned());
+
+        // Classes are loaded from anywhere in the source, even from skipped elements.
+        let class_root = component::is_class_root(class_attr);
+        let skipped = !class_root
+            && (class_attr.split_whitespace().any(|c| c == SKIP_ELEMENT_MARK)
+                || stack.last().map(|o| o.skipped).unwrap_or(false));
+
+        if class_root {
+            if let Some(id) = &id {
+                if !class_names.insert(id.to_owned()) {
+                    push(DiagnosticKind::DuplicateClass(id.to_owned()), Some(id), tag.offset);
+                }
+            } else {
+                push(DiagnosticKind::MissingRootId, None, tag.offset);
+            }
+            classes.push(ClassScope {
+                name: id.clone(),
+                ids: id.iter().cloned().collect(),
+                skipped: Default::default(),
+                refs: Default::default(),
+            });
+        } else if let Some(scope) = classes.last_mut() {
+            if skipped {
+                if let Some(id) = &id {
+                    scope.skipped.insert(id.to_owned());
+                }
+            } else {
+                if let Some(id) = &id {
+                    if !scope.ids.insert(id.to_owned()) {
+                        push(DiagnosticKind::DuplicateId(id.to_owned()), scope.name.as_ref(),
+                            tag.offset);
+                    }
+                    if is_unknown_tag(&tag.name) {
+                        push(DiagnosticKind::UnknownTag {
+                            id: id.to_owned(),
+                            tag: tag.name.clone(),
+                        }, scope.name.as_ref(), tag.offset);
+                    }
+                }
+
+                for (name, value) in &tag.attrs {
+                    if ID_REF_ATTRS.contains(&name.as_str()) {
+                        for id in value.split_whitespace() {
+                            scope.refs.push((id.to_owned(), name.to_owned(), tag.offset));
+                        }
+                    } else if name == "href" && value.starts_with('#') && value.len() > 1 {
+                        scope.refs.push((value[1..].to_owned(), name.to_owned(), tag.offset));
+                    }
+                }
+            }
+        }
+
+        if !tag.self_closing && !VOID_TAGS.contains(&tag.name.as_str()) {
+            stack.push(Open {
+                name: tag.name,
+                offset: tag.offset,
+                class_root,
+                skipped,
+            });
+        }
+    }
+
+    for open in stack {
+        push(DiagnosticKind::UnbalancedTag(open.name), None, open.offset);
+    }
+
+    diagnostics.sort_by_key(|d| d.position);
+    diagnostics
+}
+
+/// Whether the tag has no element implementation and is not a standard element. Custom
+/// elements, which names contain a hyphen, are not reported.
+fn is_unknown_tag(name: &str) -> bool {
+    matches!(TagName::from(name), TagName::Unknown(_))
+        && !name.contains('-')
+        && !HTML_TAGS.contains(&name)
+}
+
+/// Offsets at which lines start.
+fn line_starts(s: &str) -> Vec<usize> {
+    let mut vec = vec![0];
+    for (i, c) in s.char_indices() {
+        if c == '\n' {
+            vec.push(i + 1);
+        }
+    }
+    vec
+}
+
+/// Find all opening and closing tags in the source. Comments, declarations and content of
+/// scripts and styles are skipped.
+fn tags(s: &str) -> Vec<Tag> {
+    let mut vec = Vec::new();
+    let mut i = 0;
+    while let Some(found) = s[i..].find('<') {
+        let start = i + found;
+        let rest = &s[start..];
+        if rest.starts_with("<!--") {
+            i = rest.find("-->").map(|e| start + e + 3).unwrap_or(s.len());
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            i = rest.find('>').map(|e| start + e + 1).unwrap_or(s.len());
+            continue;
+        }
+
+        let closing = rest.starts_with("</");
+        let name_start = if closing { 2 } else { 1 };
+        let name_len = rest[name_start..]
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == ':'))
+            .unwrap_or(rest.len() - name_start);
+        if name_len == 0 {
+            // Not a tag, just the sign.
+            i = start + 1;
+            continue;
+        }
+        let name = rest[name_start..name_start + name_len].to_lowercase();
+
+        let (attrs, self_closing, len) = attributes(&rest[name_start + name_len..]);
+        i = start + name_start + name_len + len;
+        let raw = !closing && !self_closing && RAW_TEXT_TAGS.contains(&name.as_str());
+        vec.push(Tag {
+            name: name.clone(),
+            attrs,
+            offset: start,
+            closing,
+            self_closing,
+        });
+
+        if raw {
+            let end = s[i..].match_indices("</")
+                .find(|(e, _)| {
+                    s[i + e + 2..].get(..name.len())
+                        .map(|n| n.eq_ignore_ascii_case(&name))
+                        .unwrap_or(false)
+                })
+                .map(|(e, _)| i + e);
+            i = end.unwrap_or(s.len());
+        }
+    }
+    vec
+}
+
+/// Parse attributes of the tag up to and including `>`. Get attributes, whether tag is
+/// self-closing and the length of parsed string.
+fn attributes(s: &str) -> (Vec<(String, String)>, bool, usize) {
+    let mut attrs = Vec::new();
+    let chars: Vec<(usize, char)> = s.char_indices().collect();
+    let mut i = 0;
+    let mut self_closing = false;
+    while i < chars.len() {
+        let (_, c) = chars[i];
+        if c == '>' {
+            return (attrs, self_closing, chars[i].0 + 1);
+        }
+        if c == '/' {
+            self_closing = true;
+            i += 1;
+            continue;
+        }
+        if c.is_whitespace() {
+            i += 1;
+            continue;
+        }
+        self_closing = false;
+
+        // Attribute name.
+        let name_start = chars[i].0;
+        while i < chars.len() && !chars[i].1.is_whitespace()
+                && chars[i].1 != '=' && chars[i].1 != '>' && chars[i].1 != '/' {
+            i += 1;
+        }
+        let name_end = if i < chars.len() { chars[i].0 } else { s.len() };
+        let name = s[name_start..name_end].to_lowercase();
+
+        while i < chars.len() && chars[i].1.is_whitespace() {
+            i += 1;
+        }
+        if i >= chars.len() || chars[i].1 != '=' {
+            attrs.push((name, String::new()));
+            continue;
+        }
+        i += 1;
+        while i < chars.len() && chars[i].1.is_whitespace() {
+            i += 1;
+        }
+
+        // Attribute value.
+        let value = if i < chars.len() && (chars[i].1 == '"' || chars[i].1 == '\'') {
+            let quote = chars[i].1;
+            i += 1;
+            let value_start = if i < chars.len() { chars[i].0 } else { s.len() };
+            while i < chars.len() && chars[i].1 != quote {
+                i += 1;
+            }
+            let value_end = if i < chars.len() { chars[i].0 } else { s.len() };
+            i += 1;
+            &s[value_start..value_end]
+        } else {
+            let value_start = if i < chars.len() { chars[i].0 } else { s.len() };
+            while i < chars.len() && !chars[i].1.is_whitespace() && chars[i].1 != '>' {
+                i += 1;
+            }
+            let value_end = if i < chars.len() { chars[i].0 } else { s.len() };
+            &s[value_start..value_end]
+        };
+        attrs.push((name, value.to_owned()));
+    }
+    (attrs, self_closing, s.len())
+}
+
+/// Check all HTML files in the directory and it's subdirectories. Get diagnostics for each
+/// file that has any.
+pub fn validate_dir<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<(PathBuf, Vec<Diagnostic>)>> {
+    let mut result = Vec::new();
+    let mut entries: Vec<PathBuf> = std::fs::read_dir(path.as_ref())?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<Result<_, _>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            result.append(&mut validate_dir(&path)?);
+        } else if path.extension().map(|e| e == "html").unwrap_or(false) {
+            let html = std::fs::read_to_string(&path)?;
+            let diagnostics = validate(&html);
+            if !diagnostics.is_empty() {
+                result.push((path, diagnostics));
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// Command line entry point that checks given files and directories and prints found
+/// problems. Get exit code: 0 if there are no errors, 1 if any errors were found and
+/// 2 if files cannot be read.
+pub fn run<I: IntoIterator<Item = String>>(args: I) -> i32 {
+    let paths: Vec<String> = args.into_iter().collect();
+    if paths.is_empty() {
+        eprintln!("usage: uitaco-lint <file or directory>...");
+        return 2;
+    }
+
+    let mut found = Vec::new();
+    for path in paths {
+        let path = PathBuf::from(path);
+        let result = if path.is_dir() {
+            validate_dir(&path)
+        } else {
+            std::fs::read_to_string(&path).map(|html| vec![(path.clone(), validate(&html))])
+        };
+        match result {
+            Ok(mut vec) => found.append(&mut vec),
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                return 2;
+            },
+        }
+    }
+
+    let mut errors = 0;
+    for (path, diagnostics) in found {
+        for d in diagnostics {
+            if d.severity() == Severity::Error {
+                errors += 1;
+            }
+            println!("{}:{}", path.display(), d);
+        }
+    }
+    if errors > 0 { 1 } else { 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lint::{validate, DiagnosticKind};
+
+    #[test]
+    fn class_diagnostics() {
+        let html = "\
+<div class=\"uitacoComponent\" id=\"card\">
+    <p id=\"title\"></p>
+    <p id=\"title\"></p>
+    <div id=\"box\"><label for=\"hidden\"></label></div>
+    <dvi id=\"typo\"></dvi>
+    <span class=\"uitacoSkip\" id=\"hidden\"></span>
+</div>
+<div class=\"uitacoComponent\"></div>";
+
+        let kinds: Vec<(DiagnosticKind, usize, usize)> = validate(html).into_iter()
+            .map(|d| (d.kind, d.position.line, d.position.column))
+            .collect();
+        assert_eq!(kinds, vec![
+            (DiagnosticKind::DuplicateId("title".to_string()), 3, 5),
+            (DiagnosticKind::SkippedReference {
+                id: "hidden".to_string(),
+                attribute: "for".to_string(),
+            }, 4, 19),
+            (DiagnosticKind::UnknownTag {
+                id: "typo".to_string(),
+                tag: "dvi".to_string(),
+            }, 5, 5),
+            (DiagnosticKind::MissingRootId, 8, 1),
+        ]);
+    }
+
+    #[test]
+    fn skipped_roots() {
+        // Skipped element is not a class but classes inside of it are still loaded.
+        let html = "\
+<div class=\"uitacoComponent uitacoSkip\">
+    <div class=\"uitacoComponent\"></div>
+</div>";
+
+        let kinds: Vec<(DiagnosticKind, usize, usize)> = validate(html).into_iter()
+            .map(|d| (d.kind, d.position.line, d.position.column))
+            .collect();
+        assert_eq!(kinds, vec![(DiagnosticKind::MissingRootId, 2, 5)]);
+    }
+}
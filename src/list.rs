@@ -0,0 +1,299 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Debug, Formatter};
+use std::hash::Hash;
+use crate::tags::Element;
+use crate::component::{ClassHandle, Component, ComponentHandle, InstanceBuilder, MountError,
+    MountPosition};
+
+/// Function that gives the key of the item.
+pub type KeyFn<T, K> = dyn Fn(&T) -> K;
+
+/// Function that creates the component for the item from the builder of the list class.
+/// Builder already uses names generated with the ID strategy of the view.
+pub type RenderFn<T> = dyn Fn(&T, InstanceBuilder) -> Box<dyn Component>;
+
+/// List of components of the same class rendered from the items of a vector and mounted to
+/// the anchor of the parent component. Items are identified by keys so when the list is
+/// updated only components of inserted, removed, moved or changed items are touched.
+pub struct ListContainer<T, K> {
+    class: ClassHandle,
+    anchor: String,
+    key: Box<KeyFn<T, K>>,
+    render: Box<RenderFn<T>>,
+
+    /// Items in the order they are shown.
+    items: Vec<Item<T, K>>,
+}
+
+/// Item of the list with the component that shows it.
+struct Item<T, K> {
+    key: K,
+    value: T,
+    handle: ComponentHandle,
+}
+
+/// Error that occur while updating the list.
+#[derive(Debug)]
+pub enum ListError {
+
+    /// Two items have the same key.
+    DuplicateKey,
+
+    /// Component cannot be mounted to the anchor.
+    Mount(MountError),
+}
+
+impl<T, K> ListContainer<T, K>
+        where T: Clone + PartialEq, K: Clone + Eq + Hash {
+
+    /// Create empty list that mounts components of the class to the anchor with given name.
+    pub fn new(class: ClassHandle, anchor: String, key: Box<KeyFn<T, K>>,
+               render: Box<RenderFn<T>>) -> Self {
+        ListContainer {
+            class,
+            anchor,
+            key,
+            render,
+            items: Default::default(),
+        }
+    }
+
+    pub fn class(&self) -> &ClassHandle {
+        &self.class
+    }
+
+    pub fn anchor(&self) -> &String {
+        &self.anchor
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    /// Keys of the items in the order they are shown.
+    pub fn keys(&self) -> Vec<&K> {
+        self.items.iter().map(|i| &i.key).collect()
+    }
+
+    /// Component of the item with given key.
+    pub fn component(&self, key: &K) -> Option<&ComponentHandle> {
+        self.items.iter().find(|i| &i.key == key).map(|i| &i.handle)
+    }
+
+    /// Show new items in the anchor of the parent. Components of removed items get unmounted,
+    /// new items get mounted, kept items are moved if their order changed and changed items
+    /// are rendered again. The smallest number of kept items is moved.
+    pub fn update(&mut self, parent: &mut dyn Component, items: &[T]) -> Result<(), ListError> {
+        let keys: Vec<K> = items.iter().map(|i| (self.key)(i)).collect();
+        let mut key_set = HashSet::with_capacity(keys.len());
+        for key in &keys {
+            if !key_set.insert(key) {
+                return Err(ListError::DuplicateKey);
+            }
+        }
+
+        // Remove items that are gone.
+        let mut kept = Vec::with_capacity(self.items.len());
+        for item in self.items.drain(..) {
+            if key_set.contains(&item.key) {
+                kept.push(item);
+            } else {
+                parent.unmount(&item.handle);
+            }
+        }
+        self.items = kept;
+
+        // Render again items that have changed.
+        let mut old: HashMap<K, usize> = HashMap::with_capacity(self.items.len());
+        for (i, item) in self.items.iter().enumerate() {
+            old.insert(item.key.clone(), i);
+        }
+        for (key, value) in keys.iter().zip(items) {
+            if let Some(&i) = old.get(key) {
+                if &self.items[i].value != value {
+                    let handle = self.items[i].handle.clone();
+                    let new = self.mount(parent, value, MountPosition::After(handle.clone()))?;
+                    parent.unmount(&handle);
+                    self.items[i].handle = new;
+                    self.items[i].value = value.clone();
+                }
+            }
+        }
+
+        // Kept items that form the longest increasing sequence of old positions stay
+        // in place, all others are moved.
+        let positions: Vec<usize> = keys.iter().filter_map(|k| old.get(k).cloned()).collect();
+        let stable: HashSet<usize> = longest_increasing(&positions).into_iter()
+            .map(|i| positions[i])
+            .collect();
+
+        let mut old_items: Vec<Option<Item<T, K>>> = self.items.drain(..).map(Some).collect();
+        let mut prev: Option<ComponentHandle> = None;
+        let mut result = Ok(());
+        for (key, value) in keys.into_iter().zip(items) {
+            let position = match &prev {
+                Some(prev) => MountPosition::After(prev.clone()),
+                None => MountPosition::Prepend,
+            };
+
+            let item = if let Some(&i) = old.get(&key) {
+                let item = old_items[i].take().unwrap();
+                if !stable.contains(&i) {
+                    if let Err(e) = parent.move_mounted(&item.handle, position) {
+                        result = Err(ListError::Mount(e));
+                        self.items.push(item);
+                        break;
+                    }
+                }
+                item
+            } else {
+                match self.mount(parent, value, position) {
+                    Ok(handle) => Item {
+                        key,
+                        value: value.clone(),
+                        handle,
+                    },
+                    Err(e) => {
+                        result = Err(e);
+                        break;
+                    },
+                }
+            };
+            prev = Some(item.handle.clone());
+            self.items.push(item);
+        }
+
+        // Keep track of components that are still mounted if update was interrupted.
+        self.items.extend(old_items.into_iter().filter_map(|i| i));
+        result
+    }
+
+    /// Unmount components of all items.
+    pub fn clear(&mut self, parent: &mut dyn Component) {
+        for item in self.items.drain(..) {
+            parent.unmount(&item.handle);
+        }
+    }
+
+    /// Render the item and mount it to the position in the anchor.
+    fn mount(&self, parent: &mut dyn Component, value: &T, position: MountPosition)
+            -> Result<ComponentHandle, ListError> {
+        let mut builder = InstanceBuilder::new_for_handle(self.class.clone());
+        builder.use_view_names(parent.view());
+        let component = (self.render)(value, builder);
+        parent.mount(&self.anchor, component, position).map_err(|e| ListError::Mount(e))
+    }
+}
+
+impl<T: Debug, K: Debug> Debug for ListContainer<T, K> {
+
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        let items: Vec<(&K, &T)> = self.items.iter().map(|i| (&i.key, &i.value)).collect();
+        fmt.debug_struct("ListContainer")
+            .field("class", self.class.name())
+            .field("anchor", &self.anchor)
+            .field("items", &items)
+            .finish()
+    }
+}
+
+/// Indices of the longest strictly increasing subsequence.
+fn longest_increasing(seq: &[usize]) -> Vec<usize> {
+    // Index of the smallest tail element for each subsequence length.
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev: Vec<Option<usize>> = vec![None; seq.len()];
+    for (i, &v) in seq.iter().enumerate() {
+        let pos = match tails.binary_search_by(|&t| seq[t].cmp(&v)) {
+            Ok(pos) | Err(pos) => pos,
+        };
+        if pos > 0 {
+            prev[i] = Some(tails[pos - 1]);
+        }
+        if pos == tails.len() {
+            tails.push(i);
+        } else {
+            tails[pos] = i;
+        }
+    }
+
+    let mut result = Vec::with_capacity(tails.len());
+    let mut cur = tails.last().cloned();
+    while let Some(i) = cur {
+        result.push(i);
+        cur = prev[i];
+    }
+    result.reverse();
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use crate::View;
+    use crate::component::{Class, Component, InstanceBuilder, COMPONENT_MARK};
+    use crate::list::{longest_increasing, ListContainer, ListError};
+
+    #[test]
+    fn longest_increasing_positions() {
+        assert_eq!(longest_increasing(&[]), Vec::<usize>::new());
+        assert_eq!(longest_increasing(&[0, 1, 2]), vec![0, 1, 2]);
+        assert_eq!(longest_increasing(&[2, 0, 1]), vec![1, 2]);
+        assert_eq!(longest_increasing(&[3, 1, 2, 0, 4]), vec![1, 2, 4]);
+    }
+
+    #[test]
+    fn update_items() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"item\">{{{{label}}}}</div>\
+            <div class=\"{mark}\" id=\"card\">\
+                <ul data-uitaco-anchor=\"items\"></ul>\
+            </div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let item = classes.remove("item").unwrap().into_handle();
+        let mut card = classes.remove("card").unwrap().into_builder();
+        card.use_view_names(&view);
+        let mut card = card.build(view.clone());
+
+        let render_view = view.clone();
+        let mut list = ListContainer::new(item, "items".to_string(),
+            Box::new(|item: &(u32, &'static str)| item.0),
+            Box::new(move |item: &(u32, &'static str), builder: InstanceBuilder| {
+                let mut props = HashMap::new();
+                props.insert("label", item.1);
+                let base = builder.with_props(&props).unwrap().build(render_view.clone());
+                Box::new(base) as Box<dyn Component>
+            }));
+
+        list.update(&mut card, &[(1, "one"), (2, "two"), (3, "three")]).unwrap();
+        let old = card.mounted("items").unwrap().clone();
+        assert_eq!(list.keys(), vec![&1, &2, &3]);
+        assert_eq!(old.len(), 3);
+
+        // Item 3 is moved to the front, item 2 is rendered again and item 1 stays.
+        list.update(&mut card, &[(3, "three"), (1, "one"), (2, "TWO")]).unwrap();
+        let new = card.mounted("items").unwrap().clone();
+        assert_eq!(list.keys(), vec![&3, &1, &2]);
+        assert_eq!(new.len(), 3);
+        assert_eq!((&new[0], &new[1]), (&old[2], &old[0]));
+        assert_ne!(new[2], old[1]);
+        assert!(new[2].read().generated_html().to_string().contains("TWO"));
+
+        match list.update(&mut card, &[(1, "one"), (1, "uno")]) {
+            Err(ListError::DuplicateKey) => (),
+            _ => panic!("duplicate keys were accepted"),
+        }
+        assert_eq!(card.mounted("items").unwrap(), &new);
+
+        list.update(&mut card, &[(1, "one")]).unwrap();
+        assert_eq!(card.mounted("items").unwrap(), &vec![old[0].clone()]);
+        list.clear(&mut card);
+        assert!(card.mounted("items").unwrap().is_empty());
+    }
+}
@@ -0,0 +1,333 @@
+use std::sync::{Arc, Mutex};
+use std::fmt::{Debug, Formatter};
+use serde::Serialize;
+use serde_json::Value;
+use crate::ViewWrap;
+use crate::tags::Element;
+use crate::template;
+
+/// Attribute of the element in class HTML that binds it to named observables:
+/// `data-uitaco-bind="text: title; attr.href: url; class.active: selected; visible: shown"`.
+pub const BIND_ATTR: &'static str = "data-uitaco-bind";
+
+/// What part of the element follows the value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BindKind {
+
+    /// Text content is set to the value.
+    Text,
+
+    /// Attribute with given name is set to the value.
+    Attribute(String),
+
+    /// Class with given name is present while the value is truthy.
+    Class(String),
+
+    /// Element is shown while the value is truthy.
+    Visible,
+}
+
+/// Connection of the observable value to the elements on the page.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+
+    /// CSS selector of the bound elements.
+    selector: String,
+
+    kind: BindKind,
+}
+
+/// Value that updates all bound elements when it changes. Clones share the same value
+/// and bindings.
+pub struct Observable<T: Serialize> {
+    inner: Arc<Mutex<Inner<T>>>,
+}
+
+struct Inner<T> {
+    view: ViewWrap,
+    value: T,
+    bindings: Vec<Binding>,
+}
+
+impl BindKind {
+
+    /// Parse the kind as it is written in `data-uitaco-bind` attribute.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        if s == "text" {
+            Some(BindKind::Text)
+        } else if s == "visible" {
+            Some(BindKind::Visible)
+        } else if s.starts_with("attr.") {
+            Some(BindKind::Attribute(s[5..].to_owned()))
+        } else if s.starts_with("class.") {
+            Some(BindKind::Class(s[6..].to_owned()))
+        } else {
+            None
+        }
+    }
+}
+
+impl Binding {
+
+    /// Bind elements that match the selector.
+    pub fn new(selector: String, kind: BindKind) -> Self {
+        Binding {
+            selector,
+            kind,
+        }
+    }
+
+    /// Bind the element.
+    pub fn for_element(element: &dyn Element, kind: BindKind) -> Self {
+        Self::new(format!("#{}", element.id()), kind)
+    }
+
+    pub fn selector(&self) -> &String {
+        &self.selector
+    }
+
+    pub fn kind(&self) -> &BindKind {
+        &self.kind
+    }
+
+    /// JS code that applies the value to the bound elements.
+    pub fn js(&self, value: &Value) -> String {
+        let action = match &self.kind {
+            BindKind::Text => format!(
+                "e.textContent = {};",
+                Value::String(template::value_to_string(value))
+            ),
+            BindKind::Attribute(name) => format!(
+                "e.setAttribute({}, {});",
+                Value::String(name.to_owned()), Value::String(template::value_to_string(value))
+            ),
+            BindKind::Class(name) => format!(
+                "e.classList.toggle({}, {});",
+                Value::String(name.to_owned()), template::is_truthy(value)
+            ),
+            BindKind::Visible => format!("e.hidden = {};", !template::is_truthy(value)),
+        };
+
+        format!("\
+            document.querySelectorAll({}).forEach(function(e) {{ {} }});\
+        ", Value::String(self.selector.to_owned()), action)
+    }
+}
+
+/// Parse `data-uitaco-bind` attribute into pairs of bind kind and observable name.
+/// Unknown kinds are skipped.
+pub fn parse_bindings(s: &str) -> Vec<(BindKind, String)> {
+    let mut vec = Vec::new();
+    for part in s.split(';') {
+        let mut split = part.splitn(2, ':');
+        let kind = split.next().and_then(BindKind::parse);
+        let name = split.next().map(|s| s.trim());
+        if let (Some(kind), Some(name)) = (kind, name) {
+            if !name.is_empty() {
+                vec.push((kind, name.to_owned()));
+            }
+        }
+    }
+    vec
+}
+
+impl<T: Serialize> Observable<T> {
+
+    pub fn new(view: ViewWrap, value: T) -> Self {
+        Observable {
+            inner: Arc::new(Mutex::new(Inner {
+                view,
+                value,
+                bindings: Default::default(),
+            })),
+        }
+    }
+
+    /// Get copy of the value.
+    pub fn get(&self) -> T where T: Clone {
+        self.inner.lock().unwrap().value.clone()
+    }
+
+    /// Access the value without copying it.
+    pub fn with<R, F: FnOnce(&T) -> R>(&self, f: F) -> R {
+        f(&self.inner.lock().unwrap().value)
+    }
+
+    /// Set new value and update all bound elements.
+    pub fn set(&self, value: T) {
+        self.update(|v| *v = value)
+    }
+
+    /// Change the value in place and update all bound elements.
+    pub fn update<F: FnOnce(&mut T)>(&self, f: F) {
+        let (view, js) = {
+            let mut inner = self.inner.lock().unwrap();
+            f(&mut inner.value);
+            (inner.view.clone(), inner.js(&inner.bindings))
+        };
+        if !js.is_empty() {
+            view.eval_batched(js);
+        }
+    }
+
+    /// Add new binding. Bound elements get current value right away.
+    pub fn bind(&self, binding: Binding) {
+        let (view, js) = {
+            let mut inner = self.inner.lock().unwrap();
+            let js = inner.js(std::slice::from_ref(&binding));
+            inner.bindings.push(binding);
+            (inner.view.clone(), js)
+        };
+        view.eval_batched(js);
+    }
+
+    pub fn bind_text(&self, element: &dyn Element) {
+        self.bind(Binding::for_element(element, BindKind::Text))
+    }
+
+    pub fn bind_attribute(&self, element: &dyn Element, name: &str) {
+        self.bind(Binding::for_element(element, BindKind::Attribute(name.to_owned())))
+    }
+
+    pub fn bind_class(&self, element: &dyn Element, class: &str) {
+        self.bind(Binding::for_element(element, BindKind::Class(class.to_owned())))
+    }
+
+    pub fn bind_visible(&self, element: &dyn Element) {
+        self.bind(Binding::for_element(element, BindKind::Visible))
+    }
+
+    /// Remove all bindings that use given selector.
+    pub fn unbind(&self, selector: &str) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.bindings.retain(|b| b.selector != selector);
+    }
+
+    /// Remove all bindings.
+    pub fn unbind_all(&self) {
+        self.inner.lock().unwrap().bindings.clear();
+    }
+
+    /// All bindings of this value.
+    pub fn bindings(&self) -> Vec<Binding> {
+        self.inner.lock().unwrap().bindings.clone()
+    }
+}
+
+impl<T: Serialize> Inner<T> {
+
+    /// JS code that applies current value to given bindings.
+    fn js(&self, bindings: &[Binding]) -> String {
+        if bindings.is_empty() {
+            return String::new();
+        }
+
+        let value = serde_json::to_value(&self.value).unwrap_or(Value::Null);
+        let mut js = String::new();
+        for b in bindings {
+            js.push_str(&b.js(&value));
+        }
+        js
+    }
+}
+
+impl<T: Serialize> Clone for Observable<T> {
+
+    fn clone(&self) -> Self {
+        Observable {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T: Serialize + Debug> Debug for Observable<T> {
+
+    fn fmt(&self, fmt: &mut Formatter) -> std::fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        fmt.debug_struct("Observable")
+            .field("value", &inner.value)
+            .field("bindings", &inner.bindings)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use serde_json::Value;
+    use crate::View;
+    use crate::component::{Class, COMPONENT_MARK};
+    use crate::observable::{parse_bindings, BindKind, Binding, Observable, BIND_ATTR};
+
+    #[test]
+    fn bind_attribute() {
+        let vec = parse_bindings(
+            "text: title; attr.href : url;class.active:selected; bad: x; visible:");
+        assert_eq!(vec, vec![
+            (BindKind::Text, "title".to_string()),
+            (BindKind::Attribute("href".to_string()), "url".to_string()),
+            (BindKind::Class("active".to_string()), "selected".to_string()),
+        ]);
+    }
+
+    #[test]
+    fn binding_js() {
+        let text = Binding::new("#title".to_string(), BindKind::Text);
+        let js = text.js(&Value::String("say \"hi\"".to_string()));
+        assert!(js.contains("document.querySelectorAll(\"#title\")"));
+        assert!(js.contains("e.textContent = \"say \\\"hi\\\"\";"));
+
+        let attr = Binding::new("#link".to_string(), BindKind::Attribute("href".to_string()));
+        let js = attr.js(&Value::String("/home".to_string()));
+        assert!(js.contains("e.setAttribute(\"href\", \"/home\");"));
+
+        let class = Binding::new("#item".to_string(), BindKind::Class("active".to_string()));
+        assert!(class.js(&Value::Bool(true)).contains("e.classList.toggle(\"active\", true);"));
+
+        let visible = Binding::new("#item".to_string(), BindKind::Visible);
+        assert!(visible.js(&Value::Bool(false)).contains("e.hidden = true;"));
+    }
+
+    #[test]
+    fn component_bindings() {
+        let html = format!("\
+            <div class=\"{}\" id=\"card\">\
+                <p data-uitaco-bind=\"text: title; class.active: selected\"></p>\
+                <span data-uitaco-bind=\"text: title\"></span>\
+            </div>\
+        ", COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let base = Class::try_from_html(&html).unwrap().into_builder().build(view.clone());
+        let title = Observable::new(view.clone(), "Hello".to_string());
+        let selected = Observable::new(view, false);
+        assert_eq!(base.bind("title", &title), 2);
+        assert_eq!(base.bind("selected", &selected), 1);
+        assert_eq!(base.bind("missing", &selected), 0);
+
+        let bindings = title.bindings();
+        assert!(bindings.iter().all(|b| b.kind() == &BindKind::Text));
+        assert!(bindings.iter().all(|b| b.selector().contains(BIND_ATTR)
+            && b.selector().starts_with(&format!("#{}", base.name()))));
+        assert_eq!(selected.bindings()[0].kind(), &BindKind::Class("active".to_string()));
+    }
+
+    #[test]
+    fn batched_updates() {
+        let view = View::new_headless();
+        let value = Observable::new(view.clone(), 1);
+        value.bind(Binding::new("#count".to_string(), BindKind::Text));
+
+        view.batch(|| {
+            value.set(2);
+            value.update(|v| *v += 1);
+            let guard = view.inner.view.read().unwrap();
+            let js = &guard.batches.get(&thread::current().id()).unwrap().0;
+            assert!(js.contains("e.textContent = \"2\";"));
+            assert!(js.contains("e.textContent = \"3\";"));
+        });
+        assert!(view.inner.view.read().unwrap().batches.is_empty());
+        assert_eq!(value.get(), 3);
+    }
+}
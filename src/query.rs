@@ -0,0 +1,527 @@
+use htmldom_read::Node;
+use serde_json::Value;
+use crate::component::COMPONENT_MARK;
+
+/// Parsed list of CSS selectors. Supported are type, universal, ID, class and attribute
+/// selectors combined with descendant, child, next-sibling and subsequent-sibling
+/// combinators. Pseudo-classes and pseudo-elements are not supported.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    list: Vec<Complex>,
+}
+
+/// Error of parsing or running the selector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelectorError {
+
+    /// Selector has no compound selectors or one of the list is empty.
+    Empty,
+
+    /// Character at given byte offset was not expected.
+    Unexpected(usize, char),
+
+    /// Selector uses syntax that is not supported.
+    Unsupported(String),
+
+    /// Page rejected the selector or did not respond.
+    Page,
+}
+
+/// Compound selectors with combinators that precede them. Combinator of the first one
+/// is ignored.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Complex {
+    parts: Vec<(Combinator, Compound)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+    NextSibling,
+    SubsequentSibling,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Compound {
+    tag: Option<String>,
+    ids: Vec<String>,
+    classes: Vec<String>,
+    attributes: Vec<AttributeMatch>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct AttributeMatch {
+    name: String,
+    op: Option<(char, String)>,
+}
+
+/// Element of the tree with the links that are needed to match combinators.
+struct Flat<'a> {
+    node: &'a Node,
+    parent: Option<usize>,
+    prev: Option<usize>,
+    path: Vec<usize>,
+
+    /// Root of the nested component. It is only kept as a sibling of other elements.
+    nested: bool,
+}
+
+impl Selector {
+
+    pub fn parse(s: &str) -> Result<Self, SelectorError> {
+        let mut list = Vec::new();
+        for part in split_list(s) {
+            list.push(Complex::parse(part.0, part.1)?);
+        }
+        Ok(Selector { list })
+    }
+
+    /// Find all matching elements among the element and it's descendants. Get paths of
+    /// indices among element children from the given element in document order.
+    /// Empty path is the element itself. Nested components, which are descendants marked
+    /// with `COMPONENT_MARK`, and their elements are not searched.
+    pub fn select(&self, element: &Node) -> Vec<Vec<usize>> {
+        let mut flat = Vec::new();
+        flatten(element, None, Vec::new(), &mut flat);
+        (0..flat.len())
+            .filter(|&i| !flat[i].nested)
+            .filter(|&i| self.list.iter().any(|c| c.matches(&flat, c.parts.len() - 1, i)))
+            .map(|i| flat[i].path.clone())
+            .collect()
+    }
+
+    /// Find all matching elements among the element and it's descendants in document order.
+    pub fn select_nodes<'a>(&self, element: &'a Node) -> Vec<&'a Node> {
+        self.select(element).iter().map(|path| node_at(element, path).unwrap()).collect()
+    }
+}
+
+impl Complex {
+
+    /// Parse the selector that starts at given offset of the source.
+    fn parse(s: &str, offset: usize) -> Result<Self, SelectorError> {
+        let mut parts = Vec::new();
+        let mut combinator = Combinator::Descendant;
+        let mut chars = s.char_indices().peekable();
+        let mut pending: Option<Combinator> = None;
+        while let Some(&(i, c)) = chars.peek() {
+            match c {
+                ' ' | '\t' | '\n' | '\r' => {
+                    chars.next();
+                    if !parts.is_empty() && pending.is_none() {
+                        pending = Some(Combinator::Descendant);
+                    }
+                },
+                '>' | '+' | '~' => {
+                    chars.next();
+                    if parts.is_empty() || pending.map(|p| p != Combinator::Descendant)
+                            .unwrap_or(false) {
+                        return Err(SelectorError::Unexpected(offset + i, c));
+                    }
+                    pending = Some(match c {
+                        '>' => Combinator::Child,
+                        '+' => Combinator::NextSibling,
+                        _ => Combinator::SubsequentSibling,
+                    });
+                },
+                _ => {
+                    if let Some(p) = pending.take() {
+                        combinator = p;
+                    } else if !parts.is_empty() {
+                        return Err(SelectorError::Unexpected(offset + i, c));
+                    }
+                    let compound = Compound::parse(&mut chars, s, offset)?;
+                    parts.push((combinator, compound));
+                },
+            }
+        }
+
+        match pending {
+            Some(Combinator::Descendant) | None => (),
+            Some(_) => return Err(SelectorError::Empty),
+        }
+        if parts.is_empty() {
+            Err(SelectorError::Empty)
+        } else {
+            Ok(Complex { parts })
+        }
+    }
+
+    /// Whether compound selector with index `k` and all before it match the element.
+    fn matches(&self, flat: &[Flat], k: usize, i: usize) -> bool {
+        let (combinator, compound) = &self.parts[k];
+        if !compound.matches(flat[i].node) {
+            return false;
+        }
+        if k == 0 {
+            return true;
+        }
+
+        match combinator {
+            Combinator::Child => flat[i].parent
+                .map(|p| self.matches(flat, k - 1, p))
+                .unwrap_or(false),
+            Combinator::Descendant => {
+                let mut parent = flat[i].parent;
+                while let Some(p) = parent {
+                    if self.matches(flat, k - 1, p) {
+                        return true;
+                    }
+                    parent = flat[p].parent;
+                }
+                false
+            },
+            Combinator::NextSibling => flat[i].prev
+                .map(|p| self.matches(flat, k - 1, p))
+                .unwrap_or(false),
+            Combinator::SubsequentSibling => {
+                let mut prev = flat[i].prev;
+                while let Some(p) = prev {
+                    if self.matches(flat, k - 1, p) {
+                        return true;
+                    }
+                    prev = flat[p].prev;
+                }
+                false
+            },
+        }
+    }
+}
+
+impl Compound {
+
+    fn parse<I>(chars: &mut std::iter::Peekable<I>, s: &str, offset: usize)
+            -> Result<Self, SelectorError>
+            where I: Iterator<Item = (usize, char)> {
+        let mut compound = Compound::default();
+        let mut first = true;
+        while let Some(&(i, c)) = chars.peek() {
+            match c {
+                '*' if first => {
+                    chars.next();
+                },
+                '#' => {
+                    chars.next();
+                    compound.ids.push(ident(chars, offset)?);
+                },
+                '.' => {
+                    chars.next();
+                    compound.classes.push(ident(chars, offset)?);
+                },
+                '[' => {
+                    chars.next();
+                    compound.attributes.push(AttributeMatch::parse(chars, offset)?);
+                },
+                ':' => return Err(SelectorError::Unsupported(s[i..].to_owned())),
+                c if first && is_ident_char(c) => {
+                    compound.tag = Some(ident(chars, offset)?.to_ascii_lowercase());
+                },
+                ' ' | '\t' | '\n' | '\r' | '>' | '+' | '~' => break,
+                c => return Err(SelectorError::Unexpected(offset + i, c)),
+            }
+            first = false;
+        }
+        Ok(compound)
+    }
+
+    fn matches(&self, node: &Node) -> bool {
+        if let Some(tag) = &self.tag {
+            match node.tag_name() {
+                Some(name) if name.eq_ignore_ascii_case(tag) => (),
+                _ => return false,
+            }
+        }
+
+        let value = |name: &str| node.attribute_by_name(name).map(|a| a.values_to_string());
+        if !self.ids.is_empty() {
+            let id = value("id");
+            if !self.ids.iter().all(|i| id.as_ref() == Some(i)) {
+                return false;
+            }
+        }
+        if !self.classes.is_empty() {
+            let classes = value("class").unwrap_or_default();
+            let classes: Vec<&str> = classes.split_whitespace().collect();
+            if !self.classes.iter().all(|c| classes.contains(&c.as_str())) {
+                return false;
+            }
+        }
+        self.attributes.iter().all(|a| a.matches(value(&a.name)))
+    }
+}
+
+impl AttributeMatch {
+
+    /// Parse attribute selector after the opening bracket.
+    fn parse<I>(chars: &mut std::iter::Peekable<I>, offset: usize)
+            -> Result<Self, SelectorError>
+            where I: Iterator<Item = (usize, char)> {
+        skip_spaces(chars);
+        let name = ident(chars, offset)?.to_ascii_lowercase();
+        skip_spaces(chars);
+
+        let op = match chars.next() {
+            Some((_, ']')) => return Ok(AttributeMatch { name, op: None }),
+            Some((_, '=')) => '=',
+            Some((i, c)) if "~|^$*".contains(c) => {
+                match chars.next() {
+                    Some((_, '=')) => c,
+                    Some((j, c)) => return Err(SelectorError::Unexpected(offset + j, c)),
+                    None => return Err(SelectorError::Unexpected(offset + i, c)),
+                }
+            },
+            Some((i, c)) => return Err(SelectorError::Unexpected(offset + i, c)),
+            None => return Err(SelectorError::Empty),
+        };
+        skip_spaces(chars);
+
+        let value = match chars.peek() {
+            Some(&(_, q)) if q == '"' || q == '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some((_, c)) if c == q => break,
+                        Some((_, '\\')) => if let Some((_, c)) = chars.next() {
+                            value.push(c);
+                        },
+                        Some((_, c)) => value.push(c),
+                        None => return Err(SelectorError::Empty),
+                    }
+                }
+                value
+            },
+            Some(_) => ident(chars, offset)?,
+            None => return Err(SelectorError::Empty),
+        };
+        skip_spaces(chars);
+
+        match chars.next() {
+            Some((_, ']')) => Ok(AttributeMatch { name, op: Some((op, value)) }),
+            Some((i, c)) => Err(SelectorError::Unexpected(offset + i, c)),
+            None => Err(SelectorError::Empty),
+        }
+    }
+
+    fn matches(&self, value: Option<String>) -> bool {
+        let value = if let Some(value) = value {
+            value
+        } else {
+            return false;
+        };
+        let (op, expected) = if let Some(op) = &self.op {
+            op
+        } else {
+            return true;
+        };
+
+        match op {
+            '=' => &value == expected,
+            '~' => value.split_whitespace().any(|v| v == expected),
+            '|' => &value == expected || value.starts_with(&format!("{}-", expected)),
+            '^' => !expected.is_empty() && value.starts_with(expected.as_str()),
+            '$' => !expected.is_empty() && value.ends_with(expected.as_str()),
+            '*' => !expected.is_empty() && value.contains(expected.as_str()),
+            _ => false,
+        }
+    }
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()
+}
+
+/// Read identifier. Position of unexpected character is reported with given offset.
+fn ident<I>(chars: &mut std::iter::Peekable<I>, offset: usize)
+        -> Result<String, SelectorError>
+        where I: Iterator<Item = (usize, char)> {
+    let mut ident = String::new();
+    while let Some(&(_, c)) = chars.peek() {
+        if c == '\\' {
+            chars.next();
+            if let Some((_, c)) = chars.next() {
+                ident.push(c);
+            }
+        } else if is_ident_char(c) {
+            chars.next();
+            ident.push(c);
+        } else {
+            break;
+        }
+    }
+
+    if ident.is_empty() {
+        match chars.peek() {
+            Some(&(i, c)) => Err(SelectorError::Unexpected(offset + i, c)),
+            None => Err(SelectorError::Empty),
+        }
+    } else {
+        Ok(ident)
+    }
+}
+
+fn skip_spaces<I>(chars: &mut std::iter::Peekable<I>)
+        where I: Iterator<Item = (usize, char)> {
+    while let Some(&(_, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else {
+            break;
+        }
+    }
+}
+
+/// Split selector list by commas that are not inside of brackets or quotes. Get parts
+/// with their offsets.
+fn split_list(s: &str) -> Vec<(&str, usize)> {
+    let mut vec = Vec::new();
+    let mut start = 0;
+    let mut depth = 0;
+    let mut quote = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => (),
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '[') | (None, '(') => depth += 1,
+            (None, ']') | (None, ')') => depth -= 1,
+            (None, ',') if depth == 0 => {
+                vec.push((&s[start..i], start));
+                start = i + 1;
+            },
+            _ => (),
+        }
+    }
+    vec.push((&s[start..], start));
+    vec
+}
+
+fn flatten<'a>(node: &'a Node, parent: Option<usize>, path: Vec<usize>,
+        flat: &mut Vec<Flat<'a>>) {
+    let nested = parent.is_some() && node.attribute_by_name("class")
+        .map(|a| a.values().contains(&COMPONENT_MARK.to_string()))
+        .unwrap_or(false);
+    let index = flat.len();
+    flat.push(Flat {
+        node,
+        parent,
+        prev: None,
+        path: path.clone(),
+        nested,
+    });
+    if nested {
+        return;
+    }
+
+    let mut prev = None;
+    let children = node.children().iter()
+        .map(|c| &**c)
+        .filter(|c| c.tag_name().is_some());
+    for (i, child) in children.enumerate() {
+        let mut path = path.clone();
+        path.push(i);
+        let child_index = flat.len();
+        flatten(child, Some(index), path, flat);
+        flat[child_index].prev = prev;
+        prev = Some(child_index);
+    }
+}
+
+/// Element at given path of element children indices.
+pub fn node_at<'a>(element: &'a Node, path: &[usize]) -> Option<&'a Node> {
+    let mut node = element;
+    for &i in path {
+        node = node.children().iter()
+            .map(|c| &**c)
+            .filter(|c| c.tag_name().is_some())
+            .nth(i)?;
+    }
+    Some(node)
+}
+
+/// Element at given path of element children indices.
+pub fn node_at_mut<'a>(element: &'a mut Node, path: &[usize]) -> Option<&'a mut Node> {
+    let mut node = element;
+    for &i in path {
+        node = node.children_mut().iter_mut()
+            .filter_map(|c| c.try_mut())
+            .filter(|c| c.tag_name().is_some())
+            .nth(i)?;
+    }
+    Some(node)
+}
+
+/// JS code that finds elements by the selector on the page and sends their tag names and
+/// IDs as JSON array of pairs in the response to the request. If some elements have no ID
+/// then found elements are kept under the request ID in `window.uitacoQueryResults` so
+/// that `name_js` can name them. If the page rejects the selector then response is `null`.
+pub fn live_js(selector: &str, request: usize) -> String {
+    format!("\
+        var result = null;\
+        try {{\
+            var list = document.querySelectorAll({selector});\
+            result = [];\
+            for (var i = 0; i < list.length; i++) {{\
+                result.push([list[i].tagName.toLowerCase(), list[i].id]);\
+                if (!list[i].id) {{\
+                    window.uitacoQueryResults = window.uitacoQueryResults || {{}};\
+                    window.uitacoQueryResults[{request}] = list;\
+                }}\
+            }}\
+        }} catch (e) {{}}\
+        window.external.invoke(JSON.stringify({{\
+            incmd: 'attribute',\
+            request: {request},\
+            value: JSON.stringify(result)\
+        }}));\
+    ", selector = Value::String(selector.to_owned()), request = request)
+}
+
+/// JS code that gives names to the elements without ID found by `live_js` for the request.
+/// Names are given with the indices of the elements.
+pub fn name_js(request: usize, names: &[(usize, String)]) -> String {
+    let mut js = format!("var list = window.uitacoQueryResults[{}];", request);
+    for (i, name) in names {
+        js.push_str(&format!("list[{}].id = {};", i, Value::String(name.to_owned())));
+    }
+    js.push_str(&format!("delete window.uitacoQueryResults[{}];", request));
+    js
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::query::{Selector, SelectorError};
+    use htmldom_read::Node;
+
+    #[test]
+    fn select_paths() {
+        let node = Node::from_html("\
+            <div id=\"card\" class=\"box\">\
+                <p class=\"title big\">A</p>\
+                <ul>\
+                    <li data-k=\"one\">1</li>\
+                    <li data-k=\"two-x\">2</li>\
+                </ul>\
+                <p>B</p>\
+            </div>", &Default::default()).unwrap().unwrap();
+        let element = node.children().iter().map(|c| &**c)
+            .find(|c| c.tag_name().is_some())
+            .unwrap();
+
+        let select = |s: &str| Selector::parse(s).unwrap().select(element);
+        assert_eq!(select("p"), vec![vec![0], vec![2]]);
+        assert_eq!(select("#card > .title.big"), vec![vec![0]]);
+        assert_eq!(select("div li + li"), vec![vec![1, 1]]);
+        assert_eq!(select("[data-k|=two], p ~ p"), vec![vec![1, 1], vec![2]]);
+        assert_eq!(select("ul > *"), vec![vec![1, 0], vec![1, 1]]);
+        assert_eq!(select(".box"), vec![Vec::<usize>::new()]);
+
+        assert_eq!(Selector::parse("p >"), Err(SelectorError::Empty));
+        assert_eq!(Selector::parse("a, "), Err(SelectorError::Empty));
+        assert!(match Selector::parse("p:hover") {
+            Err(SelectorError::Unsupported(_)) => true,
+            _ => false,
+        });
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use crate::component::{Class, ClassHandle, ClassLoadError, InstanceBuilder};
+
+/// Separator of the namespace and the class name: `forms::textInput`.
+pub const NAMESPACE_SEPARATOR: &'static str = "::";
+
+/// Extension of the files that are loaded from directories.
+pub const HTML_EXTENSION: &'static str = "html";
+
+/// Collection of the component classes loaded from many sources. Each class is
+/// accessible by it's full name which includes the namespace.
+#[derive(Debug, Default)]
+pub struct ClassRegistry {
+    classes: HashMap<String, ClassHandle>,
+}
+
+/// Error that occur while loading classes to the registry.
+#[derive(Debug)]
+pub enum RegistryError {
+
+    /// Class with given full name is already registered.
+    Duplicate(String),
+
+    /// File or directory cannot be read.
+    Io(std::io::Error),
+
+    /// Classes cannot be loaded from the HTML.
+    Load(ClassLoadError),
+}
+
+/// Join namespace and class name into the full name.
+pub fn full_name(namespace: Option<&str>, name: &str) -> String {
+    if let Some(namespace) = namespace {
+        if !namespace.is_empty() {
+            return format!("{}{}{}", namespace, NAMESPACE_SEPARATOR, name);
+        }
+    }
+    name.to_owned()
+}
+
+impl ClassRegistry {
+
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Register the class under given namespace. Get full name of the registered class.
+    pub fn insert(&mut self, namespace: Option<&str>, mut class: Class)
+            -> Result<String, RegistryError> {
+        let name = full_name(namespace, class.name());
+        if self.classes.contains_key(&name) {
+            return Err(RegistryError::Duplicate(name));
+        }
+        class.set_full_name(name.clone());
+        self.classes.insert(name.clone(), class.into_handle());
+        Ok(name)
+    }
+
+    /// Load all classes from HTML under given namespace. Get full names of loaded classes.
+    /// If any of the classes cannot be loaded or is already registered then none of them
+    /// gets loaded.
+    pub fn load_html(&mut self, namespace: Option<&str>, html: &str)
+            -> Result<Vec<String>, RegistryError> {
+        let classes = Class::all_from_html_checked(html).map_err(|e| RegistryError::Load(e))?;
+        let namespace = namespace.map(|s| s.to_owned());
+        self.insert_all(classes.into_iter().map(|(_, c)| (namespace.clone(), c)).collect())
+    }
+
+    /// Load all classes from HTML file under given namespace.
+    pub fn load_file<P: AsRef<Path>>(&mut self, namespace: Option<&str>, path: P)
+            -> Result<Vec<String>, RegistryError> {
+        let html = std::fs::read_to_string(path).map_err(|e| RegistryError::Io(e))?;
+        self.load_html(namespace, &html)
+    }
+
+    /// Load all classes from HTML files of the directory and it's subdirectories.
+    /// Names of subdirectories are appended to given namespace, so class `textInput` in
+    /// file `forms/input.html` gets name `forms::textInput`. All files are read first, so if
+    /// any of them fails to load then none of the classes gets loaded.
+    pub fn load_dir<P: AsRef<Path>>(&mut self, namespace: Option<&str>, path: P)
+            -> Result<Vec<String>, RegistryError> {
+        let mut classes = Vec::new();
+        collect_dir(namespace, path.as_ref(), &mut classes)?;
+        self.insert_all(classes)
+    }
+
+    /// Register classes under their namespaces. If any full name is taken or repeats then
+    /// none of the classes gets registered.
+    fn insert_all(&mut self, classes: Vec<(Option<String>, Class)>)
+            -> Result<Vec<String>, RegistryError> {
+        let mut set = HashSet::with_capacity(classes.len());
+        for (namespace, class) in &classes {
+            let name = full_name(namespace.as_ref().map(|s| s.as_str()), class.name());
+            if self.classes.contains_key(&name) || !set.insert(name.clone()) {
+                return Err(RegistryError::Duplicate(name));
+            }
+        }
+
+        let mut names = Vec::with_capacity(classes.len());
+        for (namespace, class) in classes {
+            names.push(self.insert(namespace.as_ref().map(|s| s.as_str()), class)?);
+        }
+        Ok(names)
+    }
+
+    /// Get class by it's full name.
+    pub fn get(&self, name: &str) -> Option<&ClassHandle> {
+        self.classes.get(name)
+    }
+
+    /// Check whether the class with given full name is registered.
+    pub fn contains(&self, name: &str) -> bool {
+        self.classes.contains_key(name)
+    }
+
+    /// All registered classes by their full names.
+    pub fn classes(&self) -> &HashMap<String, ClassHandle> {
+        &self.classes
+    }
+
+    /// Create instance builder for the class with given full name. All registered classes
+    /// can be referenced by their full names in the class HTML.
+    pub fn builder(&self, name: &str) -> Option<InstanceBuilder> {
+        let class = self.classes.get(name)?;
+        let mut builder = InstanceBuilder::new_for_handle(class.clone());
+        for (name, class) in &self.classes {
+            builder.use_class_as(name.to_owned(), class.clone());
+        }
+        Some(builder)
+    }
+}
+
+/// Load classes from HTML files of the directory and it's subdirectories together with
+/// the namespaces they get in the registry.
+fn collect_dir(namespace: Option<&str>, path: &Path, classes: &mut Vec<(Option<String>, Class)>)
+        -> Result<(), RegistryError> {
+    let dir = std::fs::read_dir(path).map_err(|e| RegistryError::Io(e))?;
+    for entry in dir {
+        let path = entry.map_err(|e| RegistryError::Io(e))?.path();
+        if path.is_dir() {
+            let dir_name = path.file_name().unwrap().to_string_lossy();
+            let namespace = full_name(namespace, &dir_name);
+            collect_dir(Some(&namespace), &path, classes)?;
+        } else if path.extension().map(|e| e == HTML_EXTENSION).unwrap_or(false) {
+            let html = std::fs::read_to_string(&path).map_err(|e| RegistryError::Io(e))?;
+            let found = Class::all_from_html_checked(&html)
+                .map_err(|e| RegistryError::Load(e))?;
+            let namespace = namespace.map(|s| s.to_owned());
+            classes.extend(found.into_iter().map(|(_, c)| (namespace.clone(), c)));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use crate::registry::{ClassRegistry, RegistryError};
+    use crate::component::{ClassLoadError, COMPONENT_MARK};
+    use typed_html::dom::DOMTree;
+
+    #[test]
+    fn namespaced_classes() {
+        let html: DOMTree<String> = html!(
+            <body>
+                <div class=COMPONENT_MARK id="textInput"></div>
+            </body>
+        );
+        let html = html.to_string();
+
+        let mut registry = ClassRegistry::new();
+        let names = registry.load_html(Some("forms"), &html).unwrap();
+        assert_eq!(names, vec!["forms::textInput".to_string()]);
+        registry.load_html(None, &html).unwrap();
+        assert!(registry.contains("forms::textInput"));
+        assert!(registry.contains("textInput"));
+
+        match registry.load_html(Some("forms"), &html) {
+            Err(RegistryError::Duplicate(name)) => assert_eq!(name, "forms::textInput"),
+            _ => panic!(),
+        }
+    }
+
+    #[test]
+    fn checked_loading() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"item\"></div>\
+            <div class=\"{mark}\" id=\"item\"></div>\
+        ", mark = COMPONENT_MARK);
+        let mut registry = ClassRegistry::new();
+        match registry.load_html(None, &html) {
+            Err(RegistryError::Load(ClassLoadError::Duplicate(name))) => assert_eq!(name, "item"),
+            _ => panic!(),
+        }
+
+        let dir = std::env::temp_dir().join(format!("uitaco-registry-{}", std::process::id()));
+        fs::create_dir_all(dir.join("forms")).unwrap();
+        let class = |id: &str| format!("<div class=\"{}\" id=\"{}\"></div>", COMPONENT_MARK, id);
+        fs::write(dir.join("a.html"), class("list")).unwrap();
+        fs::write(dir.join("forms").join("b.html"), class("textInput")).unwrap();
+        fs::write(dir.join("forms").join("c.html"), format!("<p class=\"{}\"></p>", COMPONENT_MARK))
+            .unwrap();
+
+        match registry.load_dir(None, &dir) {
+            Err(RegistryError::Load(ClassLoadError::InvalidClass(_))) => (),
+            _ => panic!(),
+        }
+        assert!(registry.classes().is_empty());
+
+        fs::remove_file(dir.join("forms").join("c.html")).unwrap();
+        let mut names = registry.load_dir(None, &dir).unwrap();
+        names.sort();
+        assert_eq!(names, vec!["forms::textInput".to_string(), "list".to_string()]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
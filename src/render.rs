@@ -0,0 +1,172 @@
+use htmldom_read::{Node, Attribute, Children};
+use crate::component::{Component, ComponentHandle};
+use crate::template;
+
+/// Render the component with all nested sub-components into HTML code. Generated HTML of the
+/// components is used, so changes made on the page by JS code are not present. Attributes
+/// that elements set from Rust, like image data, are filled in. Sub-components that are
+/// registered but not shown in the HTML of their container are skipped.
+pub fn component(handle: &ComponentHandle) -> String {
+    let guard = handle.read();
+    render(&**guard).to_string()
+}
+
+/// Render the component into standalone HTML document with given styles.
+pub fn document(handle: &ComponentHandle, css: &[String], title: &str) -> String {
+    let node = {
+        let guard = handle.read();
+        render(&**guard)
+    };
+    let is_body = node.children().iter()
+        .find(|c| c.tag_name().is_some())
+        .map(|c| c.tag_name() == Some("body"))
+        .unwrap_or(false);
+
+    let mut doc = String::from("<!DOCTYPE html><html><head><meta charset=\"utf-8\">");
+    doc.push_str(&format!("<title>{}</title>", template::escape_html(title)));
+    for style in css {
+        doc.push_str(&format!("<style>{}</style>", escape_style(style)));
+    }
+    doc.push_str("</head>");
+    if is_body {
+        doc.push_str(&node.to_string());
+    } else {
+        doc.push_str(&format!("<body>{}</body>", node.to_string()));
+    }
+    doc.push_str("</html>");
+    doc
+}
+
+/// Render the component into root node of it's HTML.
+fn render(component: &dyn Component) -> Node {
+    let mut node = parse(&component.generated_html().to_string()).unwrap();
+
+    for element in component.elements().values() {
+        let attrs = element.static_attributes();
+        if attrs.is_empty() {
+            continue;
+        }
+        if let Some(found) = node_by_id_mut(node.children_mut(), element.id()) {
+            for (name, value) in attrs {
+                let attr = Attribute::from_name_and_values(name, vec![value]).unwrap();
+                found.overwrite_attribute(attr);
+            }
+        }
+    }
+
+    // Sub-components in the HTML are replaced with their own rendered HTML.
+    let mut handles: Vec<&ComponentHandle> = component.components().iter().collect();
+    handles.sort_by_key(|h| h.id());
+    for handle in handles {
+        let (id, html) = {
+            let guard = handle.read();
+            (guard.name().to_owned(), render(&**guard).to_string())
+        };
+        replace_by_id(node.children_mut(), &id, &html);
+    }
+    node
+}
+
+/// Break closing style tags in the CSS so it can be put into the `style` element.
+fn escape_style(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut last = 0;
+    for (i, _) in css.match_indices("</") {
+        let closes = css.get(i + 2..i + 7)
+            .map(|s| s.eq_ignore_ascii_case("style"))
+            .unwrap_or(false);
+        if closes {
+            result.push_str(&css[last..i + 1]);
+            result.push('\\');
+            last = i + 1;
+        }
+    }
+    result.push_str(&css[last..]);
+    result
+}
+
+fn parse(html: &str) -> Option<Node> {
+    let mut node = Node::from_html(html, &Default::default()).ok()??;
+    let owned = node.children().to_all_owned();
+    *node.children_mut() = owned;
+    Some(node)
+}
+
+fn node_by_id_mut<'a>(children: &'a mut Children, id: &str) -> Option<&'a mut Node> {
+    for child in children.iter_mut() {
+        let node = if let Some(node) = child.try_mut() {
+            node
+        } else {
+            continue;
+        };
+
+        let found = node.attribute_by_name("id")
+            .map(|a| a.first_value() == id)
+            .unwrap_or(false);
+        if found {
+            return Some(node);
+        }
+        if let Some(found) = node_by_id_mut(node.children_mut(), id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// Replace the node with given ID with the HTML code. Get whether node was found.
+fn replace_by_id(children: &mut Children, id: &str, html: &str) -> bool {
+    let pos = children.iter().position(|c| {
+        c.attribute_by_name("id").map(|a| a.first_value() == id).unwrap_or(false)
+    });
+    if let Some(pos) = pos {
+        let mut parts: Vec<String> = children.iter().map(|c| c.to_string()).collect();
+        parts[pos] = html.to_owned();
+        if let Some(content) = parse(&parts.concat()) {
+            *children = content.children().to_all_owned();
+        }
+        return true;
+    }
+
+    for child in children.iter_mut() {
+        if let Some(node) = child.try_mut() {
+            if replace_by_id(node.children_mut(), id, html) {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::View;
+    use crate::component::{Class, Container, COMPONENT_MARK};
+    use crate::render::escape_style;
+
+    #[test]
+    fn render_view() {
+        let html = format!("\
+            <div class=\"{mark}\" id=\"note\"><p>Hidden</p></div>\
+            <div class=\"{mark}\" id=\"card\"><p id=\"title\">Shown</p></div>\
+        ", mark = COMPONENT_MARK);
+
+        let view = View::new_headless();
+        let mut classes = Class::all_from_html(&html);
+        let mut card = classes.remove("card").unwrap().into_builder();
+        card.use_view_names(&view);
+        let mut card = card.build(view.clone());
+        let mut note = classes.remove("note").unwrap().into_builder();
+        note.use_view_names(&view);
+        card.add_component(Box::new(note.build(view.clone()))).unwrap();
+        let mut root = view.root_component();
+        root.write().add_component(Box::new(card)).unwrap();
+        view.inject_css("p::after { content: '</STYLE>'; }".to_string());
+
+        let doc = view.render_static("A & B");
+        assert!(doc.contains("<title>A &amp; B</title>"));
+        assert!(doc.contains(">Shown<"));
+        assert!(!doc.contains("Hidden"));
+        assert_eq!(doc.matches("</style>").count(), 1);
+        assert_eq!(escape_style("a</style></b>"), "a<\\/style></b>");
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use serde_derive::{Serialize, Deserialize};
+use serde_json::Value;
+use crate::ViewWrap;
+use crate::component::{Component, ComponentBase, ComponentHandle, Container, MountError};
+use crate::tags::Element;
+
+/// Saved state of the component and all it's sub-components.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+
+    /// Full name of the class of the component as it is registered in the class registry.
+    pub class: String,
+
+    /// Generated name of the component.
+    pub name: String,
+
+    /// Original IDs of the elements mapped to their generated IDs.
+    pub ids: HashMap<String, String>,
+
+    /// Generated HTML of the component.
+    pub html: String,
+
+    /// Anchor of the parent to which component is mounted.
+    #[serde(default)]
+    pub anchor: Option<String>,
+
+    /// User-defined state given by `Component::save_state`.
+    #[serde(default)]
+    pub state: Option<Value>,
+
+    /// Snapshots of sub-components. Components mounted to anchors go in the order they
+    /// are mounted.
+    #[serde(default)]
+    pub components: Vec<Snapshot>,
+}
+
+/// Function that turns restored component base into the user component.
+pub type WrapFn = dyn Fn(ComponentBase) -> Box<dyn Component>;
+
+/// Error that occur while restoring the snapshot.
+#[derive(Debug)]
+pub enum RestoreError {
+
+    /// View has no class registry.
+    NoRegistry,
+
+    /// Class is not in the registry of the view.
+    UnknownClass(String),
+
+    /// HTML of the component cannot be parsed or does not contain saved elements.
+    InvalidHtml(String),
+
+    /// Sub-component cannot be added to it's parent.
+    Mount(MountError),
+}
+
+impl Snapshot {
+
+    /// Save the component with all it's sub-components.
+    pub fn take(component: &ComponentHandle) -> Self {
+        let guard = component.read();
+        Self::take_component(&**guard)
+    }
+
+    fn take_component(component: &dyn Component) -> Self {
+        let mut children: Vec<(Option<(String, usize)>, usize, Snapshot)> = component
+            .components()
+            .iter()
+            .map(|h| {
+                let anchor = component.mounted_anchor(h);
+                let snapshot = Self::take(h);
+                (anchor, h.id(), snapshot)
+            })
+            .collect();
+        // Mounted components keep their order, others are restored in order of creation.
+        children.sort_by(|a, b| (&a.0, a.1).cmp(&(&b.0, b.1)));
+
+        Snapshot {
+            class: component.class().full_name().to_owned(),
+            name: component.name().to_owned(),
+            ids: component.elements().iter()
+                .map(|(origin, e)| (origin.to_owned(), e.id().to_owned()))
+                .collect(),
+            html: component.generated_html().to_string(),
+            anchor: None,
+            state: component.save_state(),
+            components: children.into_iter()
+                .map(|(anchor, _, mut s)| {
+                    s.anchor = anchor.map(|(a, _)| a);
+                    s
+                })
+                .collect(),
+        }
+    }
+
+    /// Restore the component and all it's sub-components in the view without adding it to
+    /// the interface. Classes are taken from the registry of the view. Function `wrap` turns
+    /// each restored component base into the user component, after which saved state is
+    /// applied to it.
+    pub fn restore(&self, view: &ViewWrap, wrap: &WrapFn)
+            -> Result<Box<dyn Component>, RestoreError> {
+        let registry = view.class_registry().ok_or(RestoreError::NoRegistry)?;
+        let class = registry.get(&self.class)
+            .ok_or_else(|| RestoreError::UnknownClass(self.class.to_owned()))?
+            .clone();
+
+        let mut base = ComponentBase::from_generated(view.clone(), class, &self.html, &self.ids)
+            .ok_or_else(|| RestoreError::InvalidHtml(self.name.to_owned()))?;
+        for child in &self.components {
+            let component = child.restore(view, wrap)?;
+            base.adopt(component, child.anchor.as_ref().map(|s| s.as_str()))
+                .map_err(|e| RestoreError::Mount(e))?;
+        }
+
+        let mut component = wrap(base);
+        if let Some(state) = &self.state {
+            component.restore_state(state.clone());
+        }
+        Ok(component)
+    }
+
+    /// Restore the component and add it to the root component of the view.
+    pub fn restore_to_root(&self, view: &ViewWrap, wrap: &WrapFn)
+            -> Result<ComponentHandle, RestoreError> {
+        let component = self.restore(view, wrap)?;
+        let mut root = view.root_component();
+        let mut guard = root.write();
+        guard.add_component(component).map_err(|e| RestoreError::Mount(MountError::Add(e)))
+    }
+
+    /// Save the snapshot to JSON document.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Load the snapshot from JSON document.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::View;
+    use crate::component::{Component, ComponentBase, COMPONENT_MARK};
+    use crate::registry::ClassRegistry;
+    use crate::snapshot::Snapshot;
+
+    #[test]
+    fn snapshot_json() {
+        let json = r#"{
+            "class": "card",
+            "name": "x1",
+            "ids": { "card": "x1", "title": "x2" },
+            "html": "<div id='x1' class='uitacoComponent'><p id='x2'></p></div>",
+            "state": { "open": true }
+        }"#;
+        let snapshot = Snapshot::from_json(json).unwrap();
+        assert_eq!(snapshot.anchor, None);
+        assert!(snapshot.components.is_empty());
+        assert_eq!(Snapshot::from_json(&snapshot.to_json()).unwrap(), snapshot);
+    }
+
+    #[test]
+    fn namespaced_round_trip() {
+        let html = format!("<div class=\"{}\" id=\"card\"><p id=\"title\"></p></div>",
+            COMPONENT_MARK);
+        let mut registry = ClassRegistry::new();
+        registry.load_html(Some("ui"), &html).unwrap();
+        let view = View::new_headless();
+        view.set_class_registry(Arc::new(registry));
+
+        let base = view.build_component("ui::card").unwrap();
+        let handle = view.register_component(Box::new(base));
+        let snapshot = Snapshot::take(&handle);
+        assert_eq!(snapshot.class, "ui::card");
+
+        let wrap = |base: ComponentBase| Box::new(base) as Box<dyn Component>;
+        let restored = snapshot.restore(&view, &wrap).unwrap();
+        assert_eq!(restored.name(), &snapshot.name);
+        assert_eq!(restored.class().full_name(), "ui::card");
+    }
+}
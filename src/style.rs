@@ -0,0 +1,184 @@
+/// Tag of the elements in class HTML that contain styles of the class.
+pub const STYLE_TAG: &'static str = "style";
+
+/// Prefix of the class that marks all elements of the component class that has styles.
+pub const SCOPE_PREFIX: &'static str = "uitacoScope-";
+
+/// Pseudo-class that can be used in styles of the class to select the component root.
+pub const SCOPE_SELECTOR: &'static str = ":scope";
+
+/// At-rules which contain other rules that must be scoped too.
+const NESTING_AT_RULES: &'static [&'static str] = &["@media", "@supports", "@document"];
+
+/// Name of the scope class for the component class with given name.
+pub fn scope_name(class: &str) -> String {
+    let mut s = String::from(SCOPE_PREFIX);
+    for c in class.chars() {
+        if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+            s.push(c);
+        } else {
+            s.push('-');
+        }
+    }
+    s
+}
+
+/// Rewrite the style sheet so it only applies to the elements marked with the scope class.
+/// Each selector must match the scope class with it's last compound selector. `:scope`
+/// is replaced by the scope class. Rules of `@media`, `@supports` and `@document` are
+/// rewritten too, other at-rules are left as is.
+pub fn scope_css(css: &str, scope: &str) -> String {
+    let css = strip_comments(css);
+    let mut out = String::with_capacity(css.len() * 2);
+    scope_rules(&css, scope, &mut out);
+    out
+}
+
+fn scope_rules(css: &str, scope: &str, out: &mut String) {
+    let mut rest = css;
+    loop {
+        let start = match rest.find(|c| c == '{' || c == ';') {
+            Some(start) => start,
+            None => {
+                out.push_str(rest.trim());
+                return;
+            },
+        };
+        let prelude = rest[..start].trim();
+
+        if rest[start..].starts_with(';') {
+            // Statement like `@import`.
+            out.push_str(prelude);
+            out.push(';');
+            rest = &rest[start + 1..];
+            continue;
+        }
+
+        let end = block_end(rest, start);
+        let body = &rest[start + 1..end];
+        if prelude.starts_with('@') {
+            out.push_str(prelude);
+            out.push('{');
+            let nesting = NESTING_AT_RULES.iter().any(|r| prelude.starts_with(r));
+            if nesting {
+                scope_rules(body, scope, out);
+            } else {
+                out.push_str(body);
+            }
+            out.push('}');
+        } else {
+            let selectors: Vec<String> = split_selectors(prelude).iter()
+                .map(|s| scope_selector(s.trim(), scope))
+                .collect();
+            out.push_str(&selectors.join(", "));
+            out.push('{');
+            out.push_str(body.trim());
+            out.push('}');
+        }
+
+        if end >= rest.len() {
+            return;
+        }
+        rest = &rest[end + 1..];
+    }
+}
+
+/// Index of the brace that closes the block opened at given index or the length of the
+/// string if block is not closed.
+fn block_end(s: &str, open: usize) -> usize {
+    let mut depth = 0;
+    for (i, c) in s[open..].char_indices() {
+        if c == '{' {
+            depth += 1;
+        } else if c == '}' {
+            depth -= 1;
+            if depth == 0 {
+                return open + i;
+            }
+        }
+    }
+    s.len()
+}
+
+/// Split selector list by commas that are not inside of parentheses or brackets.
+fn split_selectors(s: &str) -> Vec<&str> {
+    let mut vec = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                vec.push(&s[start..i]);
+                start = i + 1;
+            },
+            _ => (),
+        }
+    }
+    vec.push(&s[start..]);
+    vec
+}
+
+/// Add the scope class to the last compound selector before any pseudo-element.
+fn scope_selector(selector: &str, scope: &str) -> String {
+    let class = format!(".{}", scope);
+    if selector.contains(SCOPE_SELECTOR) {
+        return selector.replace(SCOPE_SELECTOR, &class);
+    }
+
+    // Start of the last compound selector is after the last combinator or space
+    // which is not inside of parentheses or brackets.
+    let mut depth = 0;
+    let mut last = 0;
+    for (i, c) in selector.char_indices() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth -= 1,
+            ' ' | '>' | '+' | '~' if depth == 0 => last = i + 1,
+            _ => (),
+        }
+    }
+
+    let compound = &selector[last..];
+    let insert = compound.find("::").map(|i| last + i).unwrap_or(selector.len());
+    let mut s = String::with_capacity(selector.len() + class.len());
+    s.push_str(&selector[..insert]);
+    s.push_str(&class);
+    s.push_str(&selector[insert..]);
+    s
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut out = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        rest = match rest[start + 2..].find("*/") {
+            Some(end) => &rest[start + 2 + end + 2..],
+            None => "",
+        };
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::style::{scope_css, scope_name};
+
+    #[test]
+    fn scoped_selectors() {
+        assert_eq!(scope_name("todo::item"), "uitacoScope-todo--item");
+
+        let css = "/* list */ ul > li.active:hover, a::before { color: red; }\
+            :scope { display: block }\
+            @media (max-width: 100px) { p { margin: 0 } }\
+            @keyframes spin { from { opacity: 0 } }";
+        assert_eq!(scope_css(css, "s"), "\
+            ul > li.active:hover.s, a.s::before{color: red;}\
+            .s{display: block}\
+            @media (max-width: 100px){p.s{margin: 0}}\
+            @keyframes spin{ from { opacity: 0 } }");
+    }
+}
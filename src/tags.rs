@@ -6,6 +6,9 @@ use std::marker::PhantomData;
 use std::ops::{Deref, DerefMut};
 use std::fmt::Formatter;
 use std::sync::Arc;
+use std::any::Any;
+use crate::i18n::{self, Args};
+use crate::aria::{Role, Tristate, Politeness};
 
 /// The functions that allow to load images concurrently.
 pub mod image_loader {
@@ -76,15 +79,28 @@ pub enum ImageFormat {
     Jpg,
 }
 
+/// Access to the concrete type of the element or component behind the trait object.
+/// It is implemented for all types so there is no need to implement it manually.
+/// This also includes boxes, so calling it on `Box<dyn Element>` gives the box itself.
+/// Prefer `is` and `downcast_ref` of `dyn Element` and `dyn Component` which cannot
+/// be called on the box by mistake.
+pub trait AsAny {
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
 /// Element in the HTML DOM that can be accessed by Rust interface.
-pub trait Element: Debug {
+pub trait Element: Debug + AsAny {
 
     /// Tag name of the element.
     fn tag_name(&self) -> TagName;
 
     /// HTML content of this element if it still exists.
     fn dom_html(&mut self) -> Option<String> {
-        let req = self.view_mut().new_request();
+        let owner = self.id().to_owned();
+        let req = self.view_mut().new_request().owner(owner);
         let js = format!("\
             var inner = document.getElementById('{}').outerHTML;\
             window.external.invoke(JSON.stringify({{\
@@ -119,7 +135,7 @@ pub trait Element: Debug {
         let request = unsafe {
             let this = &mut *(self as *const Self as *mut Self);
             this.view_mut().new_request()
-        };
+        }.owner(self.id().to_owned());
         let id = request.id();
 
         let js = format!("\
@@ -133,8 +149,11 @@ pub trait Element: Debug {
         ", self.id(), name, id);
 
         let receiver = request.run(js);
-        let attr = receiver.recv().unwrap();
-        if let ResponseValue::Str(s) = attr {
+        let attr = receiver.recv();
+        if let Err(_) = attr {
+            return None; // request was dropped because the element was removed.
+        }
+        if let ResponseValue::Str(s) = attr.unwrap() {
             if s == "" {
                 None
             } else {
@@ -188,6 +207,16 @@ pub trait Element: Debug {
 
     fn view(&self) -> &ViewWrap;
 
+    /// Release resources like event callbacks that this element registered in the view.
+    /// Called when component that owns the element gets removed.
+    fn release(&mut self) {}
+
+    /// Attributes that were set on the page from Rust and are not present in generated HTML
+    /// of the component. Used to render the component without the page.
+    fn static_attributes(&self) -> Vec<(String, String)> {
+        Vec::new()
+    }
+
     fn view_mut(&mut self) -> &mut ViewWrap {
         let p = self.view() as *const ViewWrap as *mut ViewWrap;
         unsafe { &mut *p }
@@ -246,6 +275,102 @@ pub trait Element: Debug {
         }
         false
     }
+
+    /// ARIA role of the element if any.
+    fn role(&self) -> Option<Role> {
+        self.attribute("role").map(|s| Role::from(s.as_str()))
+    }
+
+    fn set_role(&mut self, role: Role) {
+        self.set_attribute("role", role.name())
+    }
+
+    /// Get value of ARIA state or property. Name is given without `aria-` prefix.
+    fn aria(&self, name: &str) -> Option<String> {
+        self.attribute(&format!("aria-{}", name))
+    }
+
+    /// Set value of ARIA state or property. Name is given without `aria-` prefix.
+    fn set_aria(&mut self, name: &str, value: &str) {
+        self.set_attribute(&format!("aria-{}", name), value)
+    }
+
+    /// Set accessible name of the element.
+    fn set_label(&mut self, label: &str) {
+        self.set_aria("label", label)
+    }
+
+    /// Set elements whose text is the accessible name of this element.
+    fn set_labelled_by(&mut self, labels: &[&dyn Element]) {
+        let ids = ids_list(labels);
+        self.set_aria("labelledby", &ids)
+    }
+
+    /// Set elements whose text describes this element.
+    fn set_described_by(&mut self, descriptions: &[&dyn Element]) {
+        let ids = ids_list(descriptions);
+        self.set_aria("describedby", &ids)
+    }
+
+    fn set_expanded(&mut self, expanded: bool) {
+        self.set_aria("expanded", Tristate::from(expanded).name())
+    }
+
+    fn set_checked(&mut self, checked: Tristate) {
+        self.set_aria("checked", checked.name())
+    }
+
+    fn set_pressed(&mut self, pressed: Tristate) {
+        self.set_aria("pressed", pressed.name())
+    }
+
+    fn set_selected(&mut self, selected: bool) {
+        self.set_aria("selected", Tristate::from(selected).name())
+    }
+
+    fn set_aria_disabled(&mut self, disabled: bool) {
+        self.set_aria("disabled", Tristate::from(disabled).name())
+    }
+
+    /// Hide or show the element to assistive technologies. Does not change visual appearance.
+    fn set_aria_hidden(&mut self, hidden: bool) {
+        self.set_aria("hidden", Tristate::from(hidden).name())
+    }
+
+    /// Make this element a live region so that changes of it's content get announced.
+    fn set_live(&mut self, politeness: Politeness) {
+        self.set_aria("live", politeness.name())
+    }
+
+    /// Set position of the element in sequential keyboard navigation.
+    /// Value `-1` makes element focusable only programmatically.
+    fn set_tab_index(&mut self, index: i32) {
+        self.set_attribute("tabindex", &index.to_string())
+    }
+
+    /// Move keyboard focus to this element.
+    fn focus(&mut self) {
+        let id = self.id().to_owned();
+        self.view_mut().eval(format!("document.getElementById('{}').focus();", id));
+    }
+
+    /// Remove keyboard focus from this element.
+    fn blur(&mut self) {
+        let id = self.id().to_owned();
+        self.view_mut().eval(format!("document.getElementById('{}').blur();", id));
+    }
+}
+
+/// Space-separated list of element IDs as required by ARIA relationship attributes.
+fn ids_list(elements: &[&dyn Element]) -> String {
+    let mut s = String::new();
+    for e in elements {
+        if !s.is_empty() {
+            s.push(' ');
+        }
+        s.push_str(e.id());
+    }
+    s
 }
 
 /// Text content can be set to some text value and read this content back.
@@ -263,6 +388,23 @@ pub trait TextContent: Element {
     fn set_text<T: AsRef<str>>(&mut self, text: T) {
         self.set_attribute("textContent", text.as_ref())
     }
+
+    /// Set text translated by given key in the current locale. The key is remembered by
+    /// the element so the text gets translated again when view locale changes.
+    /// If there is no translation then the key itself is used as a text.
+    fn set_text_key(&mut self, key: &str, args: &Args) {
+        let text = if let Some(text) = self.view().translate(key, args) {
+            text
+        } else {
+            key.to_owned()
+        };
+
+        // Text is set as the property, the same way `ViewWrap::set_locale` does.
+        self.set_attribute(i18n::I18N_KEY_ATTR, key);
+        self.set_attribute(i18n::I18N_ARGS_ATTR, &i18n::args_to_json(args));
+        self.view().eval(format!("document.getElementById('{}').textContent = {};",
+            self.id(), serde_json::Value::String(text)));
+    }
 }
 
 pub trait ImageContent: Element {
@@ -362,11 +504,9 @@ pub struct Span {
     id: String,
 }
 
-elm_impl!(A);
 elm_impl!(Canvas);
 elm_impl!(H4);
 elm_impl!(H5);
-elm_impl!(Img);
 elm_impl!(Li);
 elm_impl!(P);
 elm_impl!(Span);
@@ -378,12 +518,42 @@ pub struct Unknown {
     name: String,
 }
 
+impl<T> AsAny for T where T: Any {
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+impl dyn Element {
+
+    /// Whether this element has given type.
+    pub fn is<T: Element + 'static>(&self) -> bool {
+        self.as_any().is::<T>()
+    }
+
+    /// Get this element if it has given type.
+    pub fn downcast_ref<T: Element + 'static>(&self) -> Option<&T> {
+        self.as_any().downcast_ref()
+    }
+
+    /// Get mutable element if it has given type.
+    pub fn downcast_mut<T: Element + 'static>(&mut self) -> Option<&mut T> {
+        self.as_any_mut().downcast_mut()
+    }
+}
+
 impl<T> Wrap<T> where T: Element {
 
     /// Wrap given element.
     ///
     /// # Safety
     /// Programmer must be sure this element has expected type.
+    /// Consider using `try_new` which checks the type.
     pub unsafe fn new(element: Box<dyn Element>) -> Self {
         Wrap {
             element,
@@ -392,23 +562,37 @@ impl<T> Wrap<T> where T: Element {
     }
 }
 
-impl<T> Deref for Wrap<T> where T: Element {
+impl<T> Wrap<T> where T: Element + 'static {
+
+    /// Wrap given element if it has expected type. Otherwise element is given back.
+    pub fn try_new(element: Box<dyn Element>) -> Result<Self, Box<dyn Element>> {
+        if element.is::<T>() {
+            Ok(unsafe { Self::new(element) })
+        } else {
+            Err(element)
+        }
+    }
+
+    /// Get back the wrapped element.
+    pub fn into_inner(self) -> Box<dyn Element> {
+        self.element
+    }
+}
+
+/// Wrap that was created with `new` for an element of other type panics when dereferenced.
+impl<T> Deref for Wrap<T> where T: Element + 'static {
 
-    type Target = Box<T>;
+    type Target = T;
 
-    fn deref(&self) -> &Box<T> {
-        let b = &self.element;
-        let ptr = b as *const Box<dyn Element> as *const Box<T>;
-        unsafe { &*ptr }
+    fn deref(&self) -> &T {
+        self.element.downcast_ref().expect("element has other type than the wrap")
     }
 }
 
-impl<T> DerefMut for Wrap<T> where T: Element {
+impl<T> DerefMut for Wrap<T> where T: Element + 'static {
 
-    fn deref_mut(&mut self) -> &mut Box<T> {
-        let b = &mut self.element;
-        let ptr = b as *mut Box<dyn Element> as *mut Box<T>;
-        unsafe { &mut *ptr }
+    fn deref_mut(&mut self) -> &mut T {
+        self.element.downcast_mut().expect("element has other type than the wrap")
     }
 }
 
@@ -446,6 +630,21 @@ impl From<&str> for TagName {
 
 impl TagName {
 
+    /// Tag name in lower case as it is written in HTML.
+    pub fn name(&self) -> &str {
+        match self {
+            TagName::A          => "a",
+            TagName::Canvas     => "canvas",
+            TagName::H4         => "h4",
+            TagName::H5         => "h5",
+            TagName::Img        => "img",
+            TagName::Li         => "li",
+            TagName::P          => "p",
+            TagName::Span       => "span",
+            TagName::Unknown(name) => name,
+        }
+    }
+
     /// Create implementation of the tag by it's tag name.
     pub fn new_impl(&self, view: ViewWrap, id: String) -> Box<dyn Element> {
         match self {
@@ -475,7 +674,7 @@ impl TagName {
             ),
 
             TagName::H5 => Box::new(
-                H4 {
+                H5 {
                     view,
                     id,
                 }
@@ -571,6 +770,25 @@ impl Image {
     }
 }
 
+impl Element for A {
+
+    fn view(&self) -> &ViewWrap {
+        &self.view
+    }
+
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn tag_name(&self) -> TagName {
+        TagName::A
+    }
+
+    fn release(&mut self) {
+        self.onclick.remove_callback();
+    }
+}
+
 impl A {
 
     pub fn href(&self) -> String {
@@ -594,6 +812,29 @@ impl A {
     }
 }
 
+impl Element for Img {
+
+    fn view(&self) -> &ViewWrap {
+        &self.view
+    }
+
+    fn id(&self) -> &String {
+        &self.id
+    }
+
+    fn tag_name(&self) -> TagName {
+        TagName::Img
+    }
+
+    fn static_attributes(&self) -> Vec<(String, String)> {
+        if let Some(data) = &self.data {
+            vec![("src".to_string(), data.to_img_string())]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
 impl ImageContent for Img {
 
     fn set_image(&mut self, img: Arc<Image>) {
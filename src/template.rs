@@ -0,0 +1,322 @@
+use serde_json::Value;
+
+/// Opening mark of the property reference in class HTML.
+pub const PROP_OPEN: &'static str = "{{";
+
+/// Closing mark of the property reference in class HTML.
+pub const PROP_CLOSE: &'static str = "}}";
+
+/// Suffix of the property name which marks property as optional: `{{subtitle?}}`.
+/// Missing optional property is replaced with empty string.
+pub const OPTIONAL_MARK: char = '?';
+
+/// Attribute that keeps the element only if the property is truthy:
+/// `data-uitaco-if="user.admin"`. Condition can be negated: `data-uitaco-if="!user.admin"`.
+pub const IF_ATTR: &'static str = "data-uitaco-if";
+
+/// Attribute that repeats content of the element for each item of the array property:
+/// `data-uitaco-for="items"` or `data-uitaco-for="row in items"`.
+pub const FOR_ATTR: &'static str = "data-uitaco-for";
+
+/// Name under which the item is accessible in repeated block if no other name was given.
+pub const DEFAULT_ITEM_NAME: &'static str = "item";
+
+/// Name under which the index of the item is accessible in repeated block.
+pub const INDEX_NAME: &'static str = "index";
+
+/// Property that is referenced by the class template.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prop {
+
+    /// Path to the value. Nested values are accessed with dots: `user.name`.
+    path: String,
+
+    /// Whether instance cannot be built without this property.
+    required: bool,
+}
+
+/// Error that occur while filling template with properties.
+#[derive(Debug)]
+pub enum PropsError {
+
+    /// Properties cannot be serialized to JSON.
+    Serialize(serde_json::Error),
+
+    /// Properties must be serialized to an object (struct or map).
+    NotAnObject,
+
+    /// Required property with given path is not present.
+    Missing(String),
+}
+
+impl Prop {
+
+    pub fn path(&self) -> &String {
+        &self.path
+    }
+
+    pub fn is_required(&self) -> bool {
+        self.required
+    }
+}
+
+/// Find all properties that are referenced in given string. Each property is listed once.
+/// Property is required if at least one of it's references is not optional.
+pub fn props_in(s: &str) -> Vec<Prop> {
+    let mut vec: Vec<Prop> = Vec::new();
+    for_each_ref(s, |prop| {
+        if let Some(p) = vec.iter_mut().find(|p| p.path == prop.path) {
+            p.required |= prop.required;
+        } else {
+            vec.push(prop);
+        }
+    });
+    vec
+}
+
+/// Call the function for each property reference in given string.
+fn for_each_ref<F: FnMut(Prop)>(s: &str, mut f: F) {
+    let mut rest = s;
+    while let Some(start) = rest.find(PROP_OPEN) {
+        let after = &rest[start + PROP_OPEN.len()..];
+        if let Some(end) = after.find(PROP_CLOSE) {
+            f(parse_ref(&after[..end]));
+            rest = &after[end + PROP_CLOSE.len()..];
+        } else {
+            break;
+        }
+    }
+}
+
+/// Parse content of the property reference between the marks.
+fn parse_ref(s: &str) -> Prop {
+    let s = s.trim();
+    if s.ends_with(OPTIONAL_MARK) {
+        Prop {
+            path: s[..s.len() - 1].trim_end().to_owned(),
+            required: false,
+        }
+    } else {
+        Prop {
+            path: s.to_owned(),
+            required: true,
+        }
+    }
+}
+
+/// Find value by dotted path in given JSON value. Array items are accessed by index.
+pub fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    let mut value = value;
+    for part in path.split('.') {
+        value = match value {
+            Value::Object(map) => map.get(part)?,
+            Value::Array(arr) => arr.get(part.parse::<usize>().ok()?)?,
+            _ => return None,
+        };
+    }
+    Some(value)
+}
+
+/// String presentation of the value as it is put into HTML. Null is an empty string,
+/// arrays and objects are written as JSON.
+pub fn value_to_string(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// Whether value counts as true in template conditions. Null, false, zero,
+/// empty string and empty array count as false.
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(b) => *b,
+        Value::Number(n) => n.as_f64().map(|f| f != 0.0).unwrap_or(true),
+        Value::String(s) => !s.is_empty(),
+        Value::Array(a) => !a.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+/// Evaluate condition of `data-uitaco-if` attribute. Missing property is false.
+pub fn condition(s: &str, data: &Value) -> bool {
+    let s = s.trim();
+    if s.starts_with('!') {
+        !condition(&s[1..], data)
+    } else {
+        lookup(data, s).map(is_truthy).unwrap_or(false)
+    }
+}
+
+/// Parse value of `data-uitaco-for` attribute into item name and path to the array.
+pub fn parse_for(s: &str) -> (&str, &str) {
+    let s = s.trim();
+    if let Some(pos) = s.find(" in ") {
+        (s[..pos].trim(), s[pos + 4..].trim())
+    } else {
+        (DEFAULT_ITEM_NAME, s)
+    }
+}
+
+/// Item names declared by all `data-uitaco-for` attributes in given HTML.
+pub fn item_names_in(html: &str) -> Vec<String> {
+    let mut vec = Vec::new();
+    let pattern = format!("{}=\"", FOR_ATTR);
+    let mut rest = html;
+    while let Some(start) = rest.find(&pattern) {
+        let after = &rest[start + pattern.len()..];
+        let end = after.find('"').unwrap_or(after.len());
+        let (name, _) = parse_for(&after[..end]);
+        if !vec.iter().any(|s| s == name) {
+            vec.push(name.to_owned());
+        }
+        rest = &after[end..];
+    }
+    vec
+}
+
+/// Data of one repeated block. It contains all outer values together with the item and it's index.
+pub fn item_scope(data: &Value, item_name: &str, item: Value, index: usize) -> Value {
+    let mut map = if let Value::Object(map) = data {
+        map.clone()
+    } else {
+        Default::default()
+    };
+    map.insert(item_name.to_owned(), item);
+    map.insert(INDEX_NAME.to_owned(), Value::from(index));
+    Value::Object(map)
+}
+
+/// Escape text so it can be safely put into HTML text and attribute values.
+/// Braces are escaped too so values cannot form property references.
+pub fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&'     => escaped.push_str("&amp;"),
+            '<'     => escaped.push_str("&lt;"),
+            '>'     => escaped.push_str("&gt;"),
+            '"'     => escaped.push_str("&quot;"),
+            '\''    => escaped.push_str("&#39;"),
+            '{'     => escaped.push_str("&#123;"),
+            '}'     => escaped.push_str("&#125;"),
+            _       => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Convert properties to JSON value that can be used to fill templates.
+pub fn props_to_value<T: serde::Serialize>(props: &T) -> Result<Value, PropsError> {
+    let value = serde_json::to_value(props).map_err(|e| PropsError::Serialize(e))?;
+    if value.is_object() {
+        Ok(value)
+    } else {
+        Err(PropsError::NotAnObject)
+    }
+}
+
+/// Check that all required properties are present in given value.
+pub fn check_props(props: &[Prop], value: &Value) -> Result<(), PropsError> {
+    for prop in props {
+        if prop.required && lookup(value, &prop.path).is_none() {
+            return Err(PropsError::Missing(prop.path.clone()));
+        }
+    }
+    Ok(())
+}
+
+/// Replace all property references in the string with escaped values from given data.
+/// Missing properties are replaced with empty string.
+pub fn interpolate(s: &str, data: &Value) -> String {
+    interpolate_impl(s, data, false)
+}
+
+/// Replace property references in the string with escaped values from given data.
+/// References to missing properties are kept so they can be filled later by outer data.
+pub fn interpolate_partial(s: &str, data: &Value) -> String {
+    interpolate_impl(s, data, true)
+}
+
+fn interpolate_impl(s: &str, data: &Value, keep_missing: bool) -> String {
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find(PROP_OPEN) {
+        let after = &rest[start + PROP_OPEN.len()..];
+        if let Some(end) = after.find(PROP_CLOSE) {
+            result.push_str(&rest[..start]);
+
+            let prop = parse_ref(&after[..end]);
+            if let Some(value) = lookup(data, &prop.path) {
+                result.push_str(&escape_html(&value_to_string(value)));
+            } else if keep_missing {
+                result.push_str(&rest[start..start + PROP_OPEN.len() + end + PROP_CLOSE.len()]);
+            }
+            rest = &after[end + PROP_CLOSE.len()..];
+        } else {
+            break;
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::template::*;
+
+    #[test]
+    fn find_props() {
+        let props = props_in("<p title=\"{{ title }}\">{{user.name}} {{note?}} {{title}}</p>");
+        assert_eq!(props.len(), 3);
+        assert_eq!(props[0].path(), "title");
+        assert!(props[0].is_required());
+        assert_eq!(props[1].path(), "user.name");
+        assert_eq!(props[2].path(), "note");
+        assert!(!props[2].is_required());
+    }
+
+    #[test]
+    fn interpolate_escaped() {
+        let data: Value = serde_json::from_str(
+            r#"{ "user": { "name": "<b>Max</b>" }, "count": 3 }"#
+        ).unwrap();
+        let s = interpolate("<p>{{user.name}} has {{count}}{{absent?}}</p>", &data);
+        assert_eq!(s, "<p>&lt;b&gt;Max&lt;/b&gt; has 3</p>");
+    }
+
+    #[test]
+    fn directives() {
+        let data: Value = serde_json::from_str(
+            r#"{ "admin": true, "items": [], "user": { "name": "" } }"#
+        ).unwrap();
+        assert!(condition("admin", &data));
+        assert!(!condition("!admin", &data));
+        assert!(!condition("items", &data));
+        assert!(!condition("user.name", &data));
+        assert!(!condition("absent", &data));
+
+        assert_eq!(parse_for("items"), (DEFAULT_ITEM_NAME, "items"));
+        assert_eq!(parse_for(" row in table.rows "), ("row", "table.rows"));
+        assert_eq!(item_names_in("<ul data-uitaco-for=\"row in rows\"></ul>"), vec!["row"]);
+    }
+
+    #[test]
+    fn partial_interpolation() {
+        let data: Value = serde_json::from_str(r#"{ "item": "{{x}}" }"#).unwrap();
+        let s = interpolate_partial("{{item}} {{outer}}", &data);
+        assert_eq!(s, "&#123;&#123;x&#125;&#125; {{outer}}");
+    }
+
+    #[test]
+    fn missing_required() {
+        let props = props_in("{{a}} {{b?}}");
+        let data: Value = serde_json::from_str(r#"{ "b": 1 }"#).unwrap();
+        match check_props(&props, &data) {
+            Err(PropsError::Missing(s)) => assert_eq!(s, "a"),
+            _ => panic!(),
+        }
+    }
+}